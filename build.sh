@@ -1,6 +1,7 @@
 mkdir temp
 cp -R ./text ./temp/text
 cp -R ./keymap ./temp/keymap
+cp -R ./punctuation ./temp/punctuation
 echo "Compling for Linux"
 cargo build --release
 cp ./target/release/spcli ./temp/spcli
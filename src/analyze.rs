@@ -0,0 +1,225 @@
+use std::{fmt::Write, collections::HashMap};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{pin::Document, keymap::KeyMap, render::Keyboard};
+
+const FINGERS: [&str; 8] = [
+	"left pinky", "left ring", "left middle", "left index",
+	"right index", "right middle", "right ring", "right pinky",
+];
+
+/// How a corpus is typed with a scheme, key by key
+pub struct Analysis {
+	pub name: String,
+	/// Key presses of the encoded characters
+	pub keystrokes: u32,
+	pub keys: HashMap<char, u32>,
+	/// Key presses of each finger, from the left pinky to the right pinky
+	pub fingers: [u32; 8],
+	/// Pairs of key presses with both keys on the keyboard
+	pub bigrams: u32,
+	/// Pairs of different keys pressed by the same finger
+	pub same_finger: u32,
+	/// Pairs pressed by one hand then the other
+	pub alternations: u32,
+	/// Rows crossed between the keys of each pair, summed
+	pub row_jumps: u32,
+	/// Characters the scheme has no keys for
+	pub unencodable: u32,
+	/// Effort of every key press and pair, summed as `key_cost` and `bigram_cost` do
+	pub cost: f64,
+}
+
+impl Analysis {
+	/// Types every passage of `document` with `key_map` on `keyboard`, the first keys of each syllable
+	pub fn new(document: &Document, key_map: &KeyMap, keyboard: &Keyboard) -> Self {
+		let mut analysis = Self {
+			name: key_map.name().to_string(),
+			keystrokes: 0,
+			keys: HashMap::new(),
+			fingers: [0; 8],
+			bigrams: 0,
+			same_finger: 0,
+			alternations: 0,
+			row_jumps: 0,
+			unencodable: 0,
+			cost: 0f64,
+		};
+		for passage in document.passages.iter() {
+			let mut last: Option<(char, (u16, u16))> = None;
+			for pinyin in passage.chars.iter().filter_map(|c| c.pinyin.as_ref()) {
+				let keys = match key_map.encode(&pinyin.pinyin_splitted) {
+					Some(keys) => keys,
+					None => {
+						analysis.unencodable += 1;
+						continue;
+					},
+				};
+				for key in keys {
+					analysis.keystrokes += 1;
+					*analysis.keys.entry(key).or_insert(0) += 1;
+					let position = match keyboard.position(key) {
+						Some(position) => position,
+						None => {
+							last = None;
+							continue;
+						},
+					};
+					analysis.fingers[finger(position)] += 1;
+					analysis.cost += key_cost(position);
+					if let Some((last_key, last_position)) = last {
+						analysis.bigrams += 1;
+						analysis.cost += bigram_cost(last_position, position);
+						if finger(last_position) == finger(position) && last_key != key {
+							analysis.same_finger += 1;
+						}
+						if (finger(last_position) < 4) != (finger(position) < 4) {
+							analysis.alternations += 1;
+						}
+						analysis.row_jumps += last_position.1.abs_diff(position.1) as u32;
+					}
+					last = Some((key, position));
+				}
+			}
+		}
+		analysis
+	}
+}
+
+/// The finger for a key in touch typing, by column: index fingers take the two middle
+/// columns of their hand, the pinkies whatever is beyond
+fn finger(position: (u16, u16)) -> usize {
+	match position.0 {
+		0 => 0,
+		1 => 1,
+		2 => 2,
+		3 | 4 => 3,
+		5 | 6 => 4,
+		7 => 5,
+		8 => 6,
+		_ => 7,
+	}
+}
+
+/// Effort of a key: the home row and the index fingers are the easiest, the number row the hardest
+pub fn key_cost(position: (u16, u16)) -> f64 {
+	let row = match position.1 {
+		2 => 0f64,
+		1 => 0.5,
+		3 => 1f64,
+		_ => 1.5,
+	};
+	let finger = match finger(position) {
+		3 | 4 => 0f64,
+		2 | 5 => 0.2,
+		1 | 6 => 0.5,
+		_ => 1f64,
+	};
+	1f64 + row + finger
+}
+
+/// Extra effort of a key right after another: the same finger on another key is the worst,
+/// the same hand crossing rows comes next, alternating hands costs nothing
+pub fn bigram_cost(last: (u16, u16), next: (u16, u16)) -> f64 {
+	let rows = last.1.abs_diff(next.1) as f64;
+	if last == next {
+		0.5
+	}
+	else if finger(last) == finger(next) {
+		3f64 + rows
+	}
+	else if (finger(last) < 4) == (finger(next) < 4) {
+		0.5 + 0.5 * rows
+	}
+	else {
+		0f64
+	}
+}
+
+/// The analyses side by side, then the frequency of every key pressed in `layout` order
+pub fn report(analyses: &[Analysis], layout: &[Vec<char>;4]) -> String {
+	let percent = |part: u32, total: u32| if total == 0 { String::from("-") } else { format!("{:.1}%", part as f64 / total as f64 * 100f64) };
+	let mut rows = vec![
+		[vec![String::new()], analyses.iter().map(|a| a.name.clone()).collect()].concat(),
+		[vec![String::from("keystrokes")], analyses.iter().map(|a| a.keystrokes.to_string()).collect()].concat(),
+		[vec![String::from("unencodable")], analyses.iter().map(|a| a.unencodable.to_string()).collect()].concat(),
+	];
+	for (hand, fingers) in [("left hand", 0..4), ("right hand", 4..8)] {
+		rows.push([
+			vec![hand.to_string()],
+			analyses.iter().map(|a| percent(a.fingers[fingers.clone()].iter().sum(), a.fingers.iter().sum())).collect(),
+		].concat());
+	}
+	for (i, finger) in FINGERS.iter().enumerate() {
+		rows.push([
+			vec![finger.to_string()],
+			analyses.iter().map(|a| percent(a.fingers[i], a.fingers.iter().sum())).collect(),
+		].concat());
+	}
+	rows.push([vec![String::from("same finger")], analyses.iter().map(|a| percent(a.same_finger, a.bigrams)).collect()].concat());
+	rows.push([vec![String::from("alternation")], analyses.iter().map(|a| percent(a.alternations, a.bigrams)).collect()].concat());
+	rows.push([
+		vec![String::from("row jump")],
+		analyses.iter().map(|a| if a.bigrams == 0 { String::from("-") } else { format!("{:.2}", a.row_jumps as f64 / a.bigrams as f64) }).collect(),
+	].concat());
+	rows.push([
+		vec![String::from("effort")],
+		analyses.iter().map(|a| if a.keystrokes == 0 { String::from("-") } else { format!("{:.2}", a.cost / a.keystrokes as f64) }).collect(),
+	].concat());
+
+	let mut keys: Vec<char> = layout.iter().flatten().copied().filter(|key| analyses.iter().any(|a| a.keys.contains_key(key))).collect();
+	let mut others: Vec<char> = analyses.iter().flat_map(|a| a.keys.keys()).copied().filter(|key| !keys.contains(key)).collect();
+	others.sort_unstable();
+	others.dedup();
+	keys.extend(others);
+	let mut key_rows = vec![[vec![String::from("key")], analyses.iter().map(|a| a.name.clone()).collect()].concat()];
+	for key in keys {
+		key_rows.push([
+			vec![key.to_string()],
+			analyses.iter().map(|a| percent(a.keys.get(&key).copied().unwrap_or(0), a.keystrokes)).collect(),
+		].concat());
+	}
+	format!("{}\n{}", table(&rows), table(&key_rows))
+}
+
+/// Columns aligned by display width
+fn table(rows: &[Vec<String>]) -> String {
+	let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+	let widths: Vec<usize> = (0..columns).map(|col| rows.iter().filter_map(|row| row.get(col)).map(|cell| cell.width()).max().unwrap_or(0)).collect();
+	let mut result = String::new();
+	for row in rows {
+		let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| {
+			format!("{}{}", cell, " ".repeat(width - cell.width()))
+		}).collect();
+		writeln!(result, "{}", cells.join("  ").trim_end()).unwrap();
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{pin::Passage, render::LayoutType};
+
+	#[test]
+	fn keys_are_counted_by_finger_hand_and_pair() {
+		let key_map = KeyMap::open("./keymap/xh").unwrap();
+		let keyboard = Keyboard::new(&key_map, LayoutType::Qwerty.rows());
+		// n i, h c, d e, n v: the index fingers, then the left middle finger three times
+		let document = Document { passages: vec![Passage::new(String::from("你好的，女"))] };
+		let analysis = Analysis::new(&document, &key_map, &keyboard);
+		assert_eq!(analysis.name, "小鹤双拼");
+		assert_eq!(analysis.keystrokes, 8);
+		assert_eq!(analysis.unencodable, 0);
+		assert_eq!(analysis.keys[&'n'], 2);
+		assert_eq!(analysis.fingers, [0, 0, 3, 1, 3, 1, 0, 0]);
+		assert_eq!(analysis.fingers[..4].iter().sum::<u32>(), 4);
+		assert_eq!(analysis.bigrams, 7);
+		// c d and d e
+		assert_eq!(analysis.same_finger, 2);
+		// h c, e n and n v
+		assert_eq!(analysis.alternations, 3);
+		assert_eq!(analysis.row_jumps, 2 + 1 + 1 + 1 + 1 + 2);
+	}
+}
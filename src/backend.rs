@@ -0,0 +1,277 @@
+use std::{io::{self, Write, Stdout, stdout}, fmt};
+use crossterm::{terminal, cursor::MoveTo, style::{Color, ContentStyle, StyledContent, Print, SetForegroundColor, SetBackgroundColor, SetAttribute, Attribute}, QueueableCommand};
+use unicode_width::UnicodeWidthChar;
+
+/// Somewhere `Render` can draw to
+///
+/// Coordinates are in terminal cells, `(0, 0)` being the top left corner.
+pub trait Backend {
+	/// `(width, height)`
+	fn size(&self) -> (u16, u16);
+	fn move_to(&mut self, x: u16, y: u16) -> Result<&mut Self, io::Error>;
+	/// Prints at the cursor and moves the cursor to the end of the content
+	fn print<D: fmt::Display>(&mut self, content: StyledContent<D>) -> Result<&mut Self, io::Error>;
+	/// Background of everything printed afterwards without a background of its own
+	fn set_background(&mut self, color: Color) -> Result<&mut Self, io::Error>;
+	fn clear(&mut self) -> Result<&mut Self, io::Error>;
+	fn flush(&mut self) -> Result<&mut Self, io::Error>;
+}
+
+/// The real terminal, through crossterm
+///
+/// Drawing goes to an off-screen `Grid`. `flush` compares it with what is on screen
+/// and only writes the cells that changed, so redrawing a whole frame doesn't flicker.
+pub struct Terminal {
+	stdout: Stdout,
+	/// The frame being drawn
+	screen: Grid,
+	/// The frame on screen, `None` if the screen has to be cleared first
+	shown: Option<Grid>,
+}
+
+impl Terminal {
+	pub fn new() -> Self {
+		let (width, height) = terminal::size().unwrap();
+		Self {
+			stdout: stdout(),
+			screen: Grid::new(width, height),
+			shown: None,
+		}
+	}
+
+	/// Writes the cells of `self.screen` that differ from `shown`
+	fn draw_diff(&mut self, shown: &Grid) -> Result<(), io::Error> {
+		// Where the terminal cursor is, if known
+		let mut cursor: Option<(u16, u16)> = None;
+		let mut style: Option<(Color, Color, bool)> = None;
+		for y in 0..self.screen.height {
+			for x in 0..self.screen.width {
+				let cell = self.screen.cell(x, y).unwrap();
+				if shown.cell(x, y) == Some(cell) {
+					continue;
+				}
+				let symbol = match cell.symbol {
+					Some(symbol) => symbol,
+					None => continue,
+				};
+				if cursor != Some((x, y)) {
+					self.stdout.queue(MoveTo(x, y))?;
+				}
+				if style != Some((cell.foreground, cell.background, cell.bold)) {
+					self.stdout.
+						queue(SetForegroundColor(cell.foreground))?.
+						queue(SetBackgroundColor(cell.background))?.
+						queue(SetAttribute(if cell.bold { Attribute::Bold } else { Attribute::NormalIntensity }))?;
+					style = Some((cell.foreground, cell.background, cell.bold));
+				}
+				self.stdout.queue(Print(symbol))?;
+				// Terminals disagree on the width of ambiguous characters, move explicitly after them
+				let width = symbol.width().unwrap_or(0) as u16;
+				let box_drawing = ('\u{2500}'..='\u{257f}').contains(&symbol);
+				cursor = if box_drawing || symbol.width() == symbol.width_cjk() { Some((x + width, y)) } else { None };
+			}
+		}
+		self.stdout.
+			queue(SetAttribute(Attribute::Reset))?.
+			queue(SetForegroundColor(Color::Reset))?.
+			queue(SetBackgroundColor(Color::Reset))?;
+		Ok(())
+	}
+}
+
+impl Default for Terminal {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Backend for Terminal {
+	fn size(&self) -> (u16, u16) {
+		terminal::size().unwrap()
+	}
+
+	fn move_to(&mut self, x: u16, y: u16) -> Result<&mut Self, io::Error> {
+		self.screen.move_to(x, y)?;
+		Ok(self)
+	}
+
+	fn print<D: fmt::Display>(&mut self, content: StyledContent<D>) -> Result<&mut Self, io::Error> {
+		self.screen.print(content)?;
+		Ok(self)
+	}
+
+	fn set_background(&mut self, color: Color) -> Result<&mut Self, io::Error> {
+		self.screen.set_background(color)?;
+		Ok(self)
+	}
+
+	fn clear(&mut self) -> Result<&mut Self, io::Error> {
+		let (width, height) = self.size();
+		if (width, height) != self.screen.size() {
+			// Resized, everything on screen is out of place
+			self.screen = Grid::new(width, height);
+			self.shown = None;
+		}
+		else {
+			self.screen.clear()?;
+		}
+		Ok(self)
+	}
+
+	fn flush(&mut self) -> Result<&mut Self, io::Error> {
+		let shown = match self.shown.take() {
+			Some(shown) if shown.size() == self.screen.size() => shown,
+			_ => {
+				self.stdout.queue(terminal::Clear(terminal::ClearType::All))?;
+				Grid::new(self.screen.width, self.screen.height)
+			},
+		};
+		self.draw_diff(&shown)?;
+		self.stdout.flush()?;
+		self.shown = Some(self.screen.clone());
+		Ok(self)
+	}
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Cell {
+	/// `None` for the right half of a wide character
+	pub symbol: Option<char>,
+	pub foreground: Color,
+	pub background: Color,
+	pub bold: bool,
+}
+
+impl Default for Cell {
+	fn default() -> Self {
+		Self {
+			symbol: Some(' '),
+			foreground: Color::Reset,
+			background: Color::Reset,
+			bold: false,
+		}
+	}
+}
+
+/// An in-memory screen, for rendering without a terminal
+///
+/// Its `Display` gives the text on screen, one line per row with trailing spaces trimmed,
+/// which makes it easy to compare whole screens.
+#[derive(Debug, Clone)]
+pub struct Grid {
+	width: u16,
+	height: u16,
+	cells: Vec<Cell>,
+	cursor: (u16, u16),
+	background: Color,
+}
+
+impl Grid {
+	pub fn new(width: u16, height: u16) -> Self {
+		Self {
+			width,
+			height,
+			cells: vec![Cell::default(); width as usize * height as usize],
+			cursor: (0, 0),
+			background: Color::Reset,
+		}
+	}
+
+	pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+		if x < self.width && y < self.height {
+			self.cells.get(y as usize * self.width as usize + x as usize)
+		}
+		else {
+			None
+		}
+	}
+
+	/// Text of the row `y`, trailing spaces trimmed
+	pub fn line(&self, y: u16) -> String {
+		let start = y as usize * self.width as usize;
+		self.cells[start..start + self.width as usize].iter()
+			.filter_map(|cell| cell.symbol)
+			.collect::<String>()
+			.trim_end()
+			.to_string()
+	}
+
+	fn put(&mut self, x: u16, y: u16, cell: Cell) {
+		if x < self.width && y < self.height {
+			self.cells[y as usize * self.width as usize + x as usize] = cell;
+		}
+	}
+}
+
+impl Backend for Grid {
+	fn size(&self) -> (u16, u16) {
+		(self.width, self.height)
+	}
+
+	fn move_to(&mut self, x: u16, y: u16) -> Result<&mut Self, io::Error> {
+		self.cursor = (x, y);
+		Ok(self)
+	}
+
+	fn print<D: fmt::Display>(&mut self, content: StyledContent<D>) -> Result<&mut Self, io::Error> {
+		let style: &ContentStyle = content.style();
+		let foreground = style.foreground_color.unwrap_or(Color::Reset);
+		let background = style.background_color.unwrap_or(self.background);
+		let bold = style.attributes.has(Attribute::Bold);
+		for symbol in content.content().to_string().chars() {
+			let (x, y) = self.cursor;
+			let width = symbol.width().unwrap_or(0) as u16;
+			if width == 0 {
+				continue;
+			}
+			self.put(x, y, Cell { symbol: Some(symbol), foreground, background, bold });
+			if width == 2 {
+				self.put(x + 1, y, Cell { symbol: None, foreground, background, bold });
+			}
+			self.cursor.0 = x.saturating_add(width);
+		}
+		Ok(self)
+	}
+
+	fn set_background(&mut self, color: Color) -> Result<&mut Self, io::Error> {
+		self.background = color;
+		Ok(self)
+	}
+
+	fn clear(&mut self) -> Result<&mut Self, io::Error> {
+		self.cells.fill(Cell::default());
+		Ok(self)
+	}
+
+	fn flush(&mut self) -> Result<&mut Self, io::Error> {
+		Ok(self)
+	}
+}
+
+impl fmt::Display for Grid {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for y in 0..self.height {
+			writeln!(f, "{}", self.line(y))?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crossterm::style::Stylize;
+
+	#[test]
+	fn grid_keeps_wide_characters_and_styles() {
+		let mut grid = Grid::new(8, 2);
+		grid.set_background(Color::Blue).unwrap();
+		grid.move_to(1, 1).unwrap().print("你a".with(Color::Red).bold()).unwrap();
+		assert_eq!(grid.to_string(), "\n 你a\n");
+		assert_eq!(grid.cell(1, 1), Some(&Cell { symbol: Some('你'), foreground: Color::Red, background: Color::Blue, bold: true }));
+		assert_eq!(grid.cell(2, 1).unwrap().symbol, None);
+		assert_eq!(grid.cell(3, 1).unwrap().symbol, Some('a'));
+		grid.clear().unwrap();
+		assert_eq!(grid.to_string(), "\n\n");
+	}
+}
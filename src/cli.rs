@@ -1,22 +1,237 @@
-use clap::{Parser};
+use clap::{Parser, Subcommand};
 
-use crate::render::ToneType;
+use crate::{render::{ToneType, LayoutType}, theme::ThemeType, session::Mode, i18n::Locale, hanzi::ConvertType};
 
 /// 在命令行中练习双拼
+///
+/// 未指定的选项取自配置文件 ~/.config/spcli/config.toml
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
-   /// 如何显示拼音
-   #[clap(short, long, value_parser, default_value = "live", value_name = "TYPE")]
-   pub  pinyin: ToneType,
+   #[clap(subcommand)]
+   pub command: Option<Command>,
 
-   /// 键位路径
-   #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser, default_value = "./keymap/ms")]
-   pub keymap: String,
+   /// 如何显示拼音 [default: live]
+   #[clap(short, long, value_parser, value_name = "TYPE")]
+   pub  pinyin: Option<ToneType>,
 
-   /// 文本路径
-   #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser, default_value = "./text/text.txt")]
-   pub text: String,
+   /// 键位路径 [default: ./keymap/ms]
+   #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+   pub keymap: Option<String>,
+
+   /// 文本路径, 可多次指定 [default: ./text/text.txt]
+   #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+   pub text: Vec<String>,
+
+   /// 配色方案 [default: dark]
+   #[clap(long, value_parser, value_name = "THEME")]
+   pub theme: Option<ThemeType>,
+
+   /// 键盘布局 [default: qwerty]
+   #[clap(long, value_parser, value_name = "LAYOUT")]
+   pub layout: Option<LayoutType>,
+
+   /// 练习模式 [default: shuangpin]
+   #[clap(short, long, value_parser, value_name = "MODE")]
+   pub mode: Option<Mode>,
+
+   /// 将文本转换为简体或繁体 [default: off]
+   #[clap(long, value_parser, value_name = "SCRIPT")]
+   pub convert: Option<ConvertType>,
+
+   /// 界面语言 [default: 取自 LANG]
+   #[clap(long, value_parser, value_name = "LANG")]
+   pub lang: Option<Locale>,
+
+   /// 开始输入后无操作多少秒自动暂停, 0 为不暂停 [default: 30]
+   #[clap(long, value_parser, value_name = "SECS")]
+   pub idle_pause: Option<u64>,
+
+   /// 拼音之后还需输入声调, 默认用 1-5 键, 轻声为 5
+   #[clap(long, value_parser, overrides_with = "no-tone")]
+   tone: bool,
+
+   /// 不输入声调, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "tone")]
+   no_tone: bool,
+
+   /// 英文字母和数字需逐键输入, 而不是跳过
+   #[clap(long, value_parser, overrides_with = "no-literal")]
+   literal: bool,
+
+   /// 跳过英文字母和数字, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "literal")]
+   no_literal: bool,
+
+   /// 中文标点需按输入法的按键输入, 而不是跳过
+   #[clap(long, value_parser, overrides_with = "no-punctuation")]
+   punctuation: bool,
+
+   /// 跳过中文标点, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "punctuation")]
+   no_punctuation: bool,
+
+   /// 标点按键路径 [default: ./punctuation/default]
+   #[clap(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath, value_parser)]
+   pub punctuation_map: Option<String>,
+
+   /// 查看键位表时暂停计时
+   #[clap(long, value_parser, overrides_with = "no-overlay-pause")]
+   overlay_pause: bool,
+
+   /// 查看键位表时照常计时, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "overlay-pause")]
+   no_overlay_pause: bool,
+
+   /// 同一音节连续正确输入 N 次后隐藏其拼音和按键提示, 出错后重新显示, 0 为不隐藏 [default: 0]
+   #[clap(long, value_parser, value_name = "N")]
+   pub fade: Option<u64>,
+
+   /// 提示隐藏时停顿多少秒重新显示, 0 为不显示 [default: 5]
+   #[clap(long, value_parser, value_name = "SECS")]
+   pub fade_delay: Option<u64>,
+
+   /// 文章按词分隔, 词与词之间空出一格
+   #[clap(long, value_parser, overrides_with = "no-word-spacing")]
+   word_spacing: bool,
+
+   /// 文章不按词分隔, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "word-spacing")]
+   no_word_spacing: bool,
+
+   /// 每个词输入完后需按空格上屏, 如同输入法
+   #[clap(long, value_parser, overrides_with = "no-commit")]
+   commit: bool,
+
+   /// 输入完即上屏, 无需按空格, 用于覆盖配置文件
+   #[clap(long, value_parser, overrides_with = "commit")]
+   no_commit: bool,
+}
+
+impl Args {
+   pub fn tone(&self) -> Option<bool> {
+      flag(self.tone, self.no_tone)
+   }
+
+   pub fn literal(&self) -> Option<bool> {
+      flag(self.literal, self.no_literal)
+   }
+
+   pub fn punctuation(&self) -> Option<bool> {
+      flag(self.punctuation, self.no_punctuation)
+   }
+
+   pub fn overlay_pause(&self) -> Option<bool> {
+      flag(self.overlay_pause, self.no_overlay_pause)
+   }
+
+   pub fn word_spacing(&self) -> Option<bool> {
+      flag(self.word_spacing, self.no_word_spacing)
+   }
+
+   pub fn commit(&self) -> Option<bool> {
+      flag(self.commit, self.no_commit)
+   }
+}
+
+/// `Some` when one of `--x` and `--no-x` is given, the later one winning
+fn flag(on: bool, off: bool) -> Option<bool> {
+   match (on, off) {
+      (true, _) => Some(true),
+      (_, true) => Some(false),
+      _ => None,
+   }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+   /// 配置文件
+   Config {
+      #[clap(subcommand)]
+      action: ConfigAction,
+   },
+   /// 按模式和方案对比历史成绩, 如全拼与双拼
+   History,
+   /// 复习到期的音节, 按 SM-2 安排, 以文本中读该音的字为载体
+   Review,
+   /// 用一个或多个方案编码文本, 对比按键频率、各指和左右手负担、同指连击、左右手交替和跨行距离
+   Analyze {
+      /// 文本路径, 可多次指定 [default: 同 --text]
+      #[clap(short, long, value_name = "FILE", value_hint = clap::ValueHint::FilePath, value_parser)]
+      text: Vec<String>,
+
+      /// 键位路径, 可多次指定以对比多个方案 [default: 同 --keymap]
+      #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+      keymap: Vec<String>,
+   },
+   /// 在文本上搜索按键负担最小的韵母布局, 声母、单韵母和零声母规则不变, 写出可直接练习的键位文件
+   Optimize {
+      /// 文本路径, 可多次指定 [default: 同 --text]
+      #[clap(short, long, value_name = "FILE", value_hint = clap::ValueHint::FilePath, value_parser)]
+      text: Vec<String>,
+
+      /// 作为起点的键位路径 [default: 同 --keymap]
+      #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+      keymap: Option<String>,
+
+      /// 写出的键位路径
+      #[clap(short, long, value_name = "FILE", value_hint = clap::ValueHint::FilePath, value_parser)]
+      output: String,
+
+      /// 新方案名 [default: 起点方案名加"优化"]
+      #[clap(long, value_name = "NAME", value_parser)]
+      name: Option<String>,
+
+      /// 搜索步数
+      #[clap(long, value_name = "N", value_parser, default_value = "20000")]
+      iterations: u32,
+   },
+   /// 按方案写出文本中每个字的双拼按键, 用于制作练习纸或核对输入法设置
+   Encode {
+      /// 文本路径, 省略或为 `-` 时读取标准输入
+      #[clap(value_name = "FILE", value_parser)]
+      file: Option<String>,
+
+      /// 键位路径 [default: 同 --keymap]
+      #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+      keymap: Option<String>,
+
+      /// 在每组按键前写出汉字
+      #[clap(long, value_parser)]
+      interleave: bool,
+   },
+   /// 将双拼按键还原为拼音, 标出无效的按键, 有歧义时列出全部可能
+   Decode {
+      /// 按键, 每两个键一个音节, 忽略空白; 省略时逐行读取标准输入
+      #[clap(value_name = "KEYS", value_parser)]
+      keys: Option<String>,
+
+      /// 键位路径 [default: 同 --keymap]
+      #[clap(short, long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, value_parser)]
+      keymap: Option<String>,
+   },
+   /// 从脚本读取按键, 无界面运行并打印结果
+   ///
+   /// 脚本中每个字符为一次按键, 忽略空白; <C-r> <Space> <Esc> 等为特殊按键, <lt> 为 `<`; `#` 开头的行为注释
+   Script {
+      /// 脚本路径, 省略或为 `-` 时读取标准输入
+      #[clap(value_name = "FILE", value_parser)]
+      file: Option<String>,
+
+      /// 练习的段落序号
+      #[clap(long, value_name = "INDEX", value_parser, default_value = "0")]
+      passage: usize,
+
+      /// 同时打印最终画面
+      #[clap(long, value_parser)]
+      screen: bool,
+   },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+   /// 打印生效的配置及其来源
+   Show,
 }
 
 impl clap::ValueEnum for ToneType {
@@ -31,4 +246,74 @@ impl clap::ValueEnum for ToneType {
             Self::Off => Some(clap::PossibleValue::new("off")),
         }
     }
-}
\ No newline at end of file
+}
+
+impl clap::ValueEnum for ThemeType {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Dark, Self::Light, Self::HighContrast, Self::Colorblind]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+        match self {
+            Self::Dark => Some(clap::PossibleValue::new("dark")),
+            Self::Light => Some(clap::PossibleValue::new("light")),
+            Self::HighContrast => Some(clap::PossibleValue::new("high-contrast")),
+            Self::Colorblind => Some(clap::PossibleValue::new("colorblind")),
+        }
+    }
+}
+
+impl clap::ValueEnum for LayoutType {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Qwerty, Self::Dvorak, Self::Colemak]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+        match self {
+            Self::Qwerty => Some(clap::PossibleValue::new("qwerty")),
+            Self::Dvorak => Some(clap::PossibleValue::new("dvorak")),
+            Self::Colemak => Some(clap::PossibleValue::new("colemak")),
+        }
+    }
+}
+
+impl clap::ValueEnum for Mode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Shuangpin, Self::Quanpin]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+        match self {
+            Self::Shuangpin => Some(clap::PossibleValue::new("shuangpin")),
+            Self::Quanpin => Some(clap::PossibleValue::new("quanpin")),
+        }
+    }
+}
+
+
+impl clap::ValueEnum for Locale {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::ZhCn, Self::En]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+        match self {
+            Self::ZhCn => Some(clap::PossibleValue::new("zh-CN")),
+            Self::En => Some(clap::PossibleValue::new("en")),
+        }
+    }
+}
+
+impl clap::ValueEnum for ConvertType {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Off, Self::Simplified, Self::Traditional]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<clap::PossibleValue<'a>> {
+        match self {
+            Self::Off => Some(clap::PossibleValue::new("off")),
+            Self::Simplified => Some(clap::PossibleValue::new("simplified")),
+            Self::Traditional => Some(clap::PossibleValue::new("traditional")),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{pin::{self, Passage}, keymap::KeyMap};
+
+/// Written for a character the scheme has no keys for
+const UNENCODABLE: &str = "??";
+
+/// The keys of every character of `text` under `key_map`, separated by spaces, the rest of
+/// the text as it is. With `interleave`, each character goes before its keys.
+pub fn encode(text: &str, key_map: &KeyMap, interleave: bool) -> String {
+	let passage = Passage::new(text.to_string());
+	let mut result = String::new();
+	let mut last_code = false;
+	// `Passage::new` puts a space first
+	for character in passage.chars.iter().skip(1) {
+		match character.pinyin.as_ref() {
+			Some(pinyin) => {
+				if last_code {
+					result.push(' ');
+				}
+				if interleave {
+					result.push(character.char);
+				}
+				match key_map.encode(&pinyin.pinyin_splitted) {
+					Some(keys) => result.extend(keys),
+					None => result.push_str(UNENCODABLE),
+				}
+				last_code = true;
+			},
+			None => {
+				result.push(character.char);
+				last_code = false;
+			},
+		}
+	}
+	result
+}
+
+/// The syllables typed by `keys`, two keys each, whitespace being left out. A pair that
+/// types nothing is written `[xy]`, one that types several syllables `{a|b}`.
+pub fn decode(keys: &str, key_map: &KeyMap) -> String {
+	let reversed = key_map.reverse();
+	let types = |phoneme: &str, key: char| reversed.get(&key).is_some_and(|phonemes| phonemes.iter().any(|p| p == phoneme));
+	let keys: Vec<char> = keys.chars().filter(|key| !key.is_whitespace()).collect();
+	let mut syllables = Vec::new();
+	for pair in keys.chunks(2) {
+		let candidates: Vec<String> = match *pair {
+			[first, second] => pin::syllables().iter()
+				.filter(|syllable| if syllable[0].is_empty() {
+					// Syllables without initial follow the rules of the scheme, not the map alone
+					key_map.keys(syllable, true).contains(&first) && key_map.keys(syllable, false).contains(&second)
+				}
+				else {
+					types(&syllable[0], first) && types(&syllable[1], second)
+				})
+				.map(|syllable| syllable.concat())
+				.collect(),
+			_ => Vec::new(),
+		};
+		syllables.push(match candidates.len() {
+			0 => format!("[{}]", pair.iter().collect::<String>()),
+			1 => candidates[0].clone(),
+			_ => format!("{{{}}}", candidates.join("|")),
+		});
+	}
+	syllables.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn characters_are_encoded_by_pairs_of_keys() {
+		let xh = KeyMap::open("./keymap/xh").unwrap();
+		assert_eq!(encode("你好，女略。", &xh, false), "ni hc，nv lt。");
+		assert_eq!(encode("女略学", &xh, true), "女nv 略lt 学xt");
+		// 嗯 has no final of the scheme
+		assert_eq!(encode("嗯，好", &xh, false), "??，hc");
+		let ms = KeyMap::open("./keymap/ms").unwrap();
+		assert_eq!(encode("女略", &ms, false), "ny lv");
+	}
+
+	#[test]
+	fn pairs_are_decoded_marked_or_listed() {
+		let xh = KeyMap::open("./keymap/xh").unwrap();
+		assert_eq!(decode("nvlv xt aa er", &xh), "nü lü xue a er");
+		assert_eq!(decode("bv fk n", &xh), "[bv] [fk] [n]");
+		// `o` types both `o` and `uo`
+		assert_eq!(decode("lo", &xh), "{lo|luo}");
+		let ms = KeyMap::open("./keymap/ms").unwrap();
+		assert_eq!(decode("ny lv", &ms), "nü lüe");
+		// The second keys of `ve` are read too
+		let abc = KeyMap::open("./keymap/abc").unwrap();
+		assert_eq!(decode("lv", &abc), "{lü|lüe}");
+	}
+}
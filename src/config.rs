@@ -0,0 +1,583 @@
+use std::{io, fs, env, path::PathBuf, fmt::Write, time::Duration};
+
+use clap::ValueEnum;
+
+use crate::{cli::Args, render::{ToneType, LayoutType}, theme::ThemeType, input::{KeyBinding, KeyBindings, Options}, session::{Mode, Rules}, i18n::Locale, hanzi::ConvertType};
+
+const DEFAULT_KEYMAP: &str = "./keymap/ms";
+const DEFAULT_TEXT: &str = "./text/text.txt";
+const DEFAULT_PUNCTUATION: &str = "./punctuation/default";
+const DEFAULT_IDLE_PAUSE: u64 = 30;
+const DEFAULT_FADE_DELAY: u64 = 5;
+
+/// Where a setting comes from
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Source {
+	Default,
+	ConfigFile,
+	CommandLine,
+}
+
+impl Source {
+	fn describe(&self) -> &'static str {
+		match self {
+			Source::Default		=> "default",
+			Source::ConfigFile	=> "config file",
+			Source::CommandLine	=> "command line",
+		}
+	}
+}
+
+pub struct Setting<T> {
+	pub value: T,
+	pub source: Source,
+}
+
+impl<T> Setting<T> {
+	/// command line > config file > default
+	fn pick(cli: Option<T>, file: Option<T>, default: T) -> Self {
+		if let Some(value) = cli {
+			Self { value, source: Source::CommandLine }
+		}
+		else if let Some(value) = file {
+			Self { value, source: Source::ConfigFile }
+		}
+		else {
+			Self { value: default, source: Source::Default }
+		}
+	}
+}
+
+/// Settings read from `config.toml`
+///
+/// ```toml
+/// keymap = "./keymap/xh"
+/// text   = ["./text/text.txt", "~/texts/more.txt"]
+/// convert = "traditional"
+/// pinyin = "live"
+/// theme  = "light"
+/// layout = "qwerty"
+/// mode   = "shuangpin"
+/// lang   = "en"
+/// idle_pause = 30
+/// tone = true
+/// literal = true
+/// punctuation = true
+/// punctuation_map = "./punctuation/default"
+/// overlay_pause = true
+/// fade = 3
+/// fade_delay = 5
+/// word_spacing = true
+/// commit = true
+///
+/// [keybindings]
+/// quit    = "C-q"
+/// restart = "C-r"
+/// pause   = "C-p"
+/// keymap  = "C-k"
+/// ```
+#[derive(Default)]
+pub struct Config {
+	/// `None` if there is nowhere to look for the file
+	pub path: Option<PathBuf>,
+	/// Whether the file at `path` exists
+	pub found: bool,
+	pub keymap: Option<String>,
+	pub text: Option<Vec<String>>,
+	pub convert: Option<ConvertType>,
+	pub pinyin: Option<ToneType>,
+	pub theme: Option<ThemeType>,
+	pub layout: Option<LayoutType>,
+	pub mode: Option<Mode>,
+	pub lang: Option<Locale>,
+	pub idle_pause: Option<u64>,
+	pub tone: Option<bool>,
+	pub literal: Option<bool>,
+	pub punctuation: Option<bool>,
+	pub punctuation_map: Option<String>,
+	pub overlay_pause: Option<bool>,
+	pub fade: Option<u64>,
+	pub fade_delay: Option<u64>,
+	pub word_spacing: Option<bool>,
+	pub commit: Option<bool>,
+	pub quit: Option<KeyBinding>,
+	pub restart: Option<KeyBinding>,
+	pub pause: Option<KeyBinding>,
+	pub key_map: Option<KeyBinding>,
+}
+
+impl Config {
+	/// `$XDG_CONFIG_HOME/spcli/config.toml`, falling back to `~/.config/spcli/config.toml`
+	/// (`%APPDATA%\spcli\config.toml` on Windows)
+	pub fn default_path() -> Option<PathBuf> {
+		let dir = if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+			PathBuf::from(dir)
+		}
+		else if let Some(dir) = env::var_os("APPDATA").filter(|_| cfg!(windows)) {
+			PathBuf::from(dir)
+		}
+		else {
+			PathBuf::from(env::var_os("HOME")?).join(".config")
+		};
+		Some(dir.join("spcli").join("config.toml"))
+	}
+
+	/// Reads the config file at the default path. A missing file is not an error.
+	pub fn load() -> Result<Self, io::Error> {
+		let path = match Self::default_path() {
+			Some(path) => path,
+			None => return Ok(Self::default()),
+		};
+		let file = match fs::read_to_string(&path) {
+			Ok(file) => file,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => {
+				return Ok(Self { path: Some(path), ..Self::default() });
+			},
+			Err(err) => return Err(err),
+		};
+		let mut config = Self::parse(&file).map_err(|(line, message)| {
+			io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), line, message))
+		})?;
+		config.path = Some(path);
+		config.found = true;
+		Ok(config)
+	}
+
+	/// Parses the subset of TOML the config needs, and rejects the rest of TOML, such as
+	/// inline tables, floats, dotted keys or `[[arrays of tables]]`:
+	/// - `# comments` and blank lines
+	/// - `[section]` headers
+	/// - `key = value` with a bare key, the value being a `"basic"` or `'literal'` string on
+	///   one line, an integer, `true`, `false`, or an array of strings that may span lines
+	///
+	/// Errors come with the line number they occur on.
+	fn parse(file: &str) -> Result<Self, (usize, String)> {
+		let mut config = Self::default();
+		let mut section = String::new();
+		let mut lines = file.lines().enumerate();
+		while let Some((no, line)) = lines.next() {
+			let no = no + 1;
+			let line = line.trim();
+			if line.starts_with('#') || line.is_empty() {
+				continue;
+			}
+			if line.starts_with("[[") {
+				return Err((no, String::from("Arrays of tables are not supported")));
+			}
+			if line.starts_with('[') {
+				let end = line.find(']').ok_or((no, String::from("Unclosed section header")))?;
+				let rest = line[end + 1..].trim();
+				if !rest.is_empty() && !rest.starts_with('#') {
+					return Err((no, format!("Unexpected `{}` after section header", rest)));
+				}
+				section = line[1..end].trim().to_string();
+				continue;
+			}
+			let (key, value) = line.split_once('=').ok_or((no, format!("Expected `key = value`, found `{}`", line)))?;
+			let key = key.trim();
+			if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+				return Err((no, format!("Unsupported key `{}`, only bare keys are read", key)));
+			}
+			let mut value = value.trim().to_string();
+			if value.starts_with('[') {
+				// An array goes on until its `]`, over as many lines as it takes
+				while !Value::array_closed(&value) {
+					let (_, line) = lines.next().ok_or((no, String::from("Unclosed array")))?;
+					value.push('\n');
+					value.push_str(line);
+				}
+			}
+			let value = Value::parse(&value).map_err(|message| (no, message))?;
+			match (section.as_str(), key) {
+				("", "keymap")	=> config.keymap = Some(expand_home(&value.string(key).map_err(|m| (no, m))?)),
+				("", "text")	=> config.text = Some(value.list().iter().map(|path| expand_home(path)).collect()),
+				("", "convert")	=> config.convert = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "pinyin")	=> config.pinyin = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "theme")	=> config.theme = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "layout")	=> config.layout = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "mode")	=> config.mode = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "lang")	=> config.lang = Some(value.variant(key).map_err(|m| (no, m))?),
+				("", "idle_pause")	=> config.idle_pause = Some(value.number(key).map_err(|m| (no, m))?),
+				("", "tone")	=> config.tone = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("", "literal")	=> config.literal = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("", "punctuation")	=> config.punctuation = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("", "punctuation_map")	=> config.punctuation_map = Some(expand_home(&value.string(key).map_err(|m| (no, m))?)),
+				("", "overlay_pause")	=> config.overlay_pause = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("", "fade")	=> config.fade = Some(value.number(key).map_err(|m| (no, m))?),
+				("", "fade_delay")	=> config.fade_delay = Some(value.number(key).map_err(|m| (no, m))?),
+				("", "word_spacing")	=> config.word_spacing = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("", "commit")	=> config.commit = Some(value.boolean(key).map_err(|m| (no, m))?),
+				("keybindings", "quit")		=> config.quit = Some(value.key_binding(key).map_err(|m| (no, m))?),
+				("keybindings", "restart")	=> config.restart = Some(value.key_binding(key).map_err(|m| (no, m))?),
+				("keybindings", "pause")	=> config.pause = Some(value.key_binding(key).map_err(|m| (no, m))?),
+				("keybindings", "keymap")	=> config.key_map = Some(value.key_binding(key).map_err(|m| (no, m))?),
+				("", key) => return Err((no, format!("Unknown setting `{}`", key))),
+				(section, key) => return Err((no, format!("Unknown setting `{}` in [{}]", key, section))),
+			}
+		}
+		Ok(config)
+	}
+}
+
+enum Value {
+	String(String),
+	List(Vec<String>),
+}
+
+impl Value {
+	fn parse(string: &str) -> Result<Self, String> {
+		if let Some(rest) = string.strip_prefix('[') {
+			let mut list = Vec::new();
+			let mut rest = Self::skip_blank(rest);
+			loop {
+				if let Some(remain) = rest.strip_prefix(']') {
+					Self::end(remain)?;
+					return Ok(Value::List(list));
+				}
+				let (item, remain) = Self::parse_string(rest)?;
+				list.push(item);
+				rest = Self::skip_blank(remain);
+				if let Some(remain) = rest.strip_prefix(',') {
+					rest = Self::skip_blank(remain);
+				}
+				else if !rest.starts_with(']') {
+					return Err(String::from("Expected `,` or `]` in array"));
+				}
+			}
+		}
+		else if string.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '+') {
+			// Integers and booleans
+			let end = string.find(|c: char| c.is_whitespace() || c == '#').unwrap_or(string.len());
+			let value = &string[..end];
+			if value != "true" && value != "false" && value.parse::<i64>().is_err() {
+				return Err(format!("Unsupported value `{}`, expected a quoted string, an integer, true or false", value));
+			}
+			Self::end(&string[end..])?;
+			Ok(Value::String(value.to_string()))
+		}
+		else if string.starts_with("\"\"\"") || string.starts_with("'''") {
+			Err(String::from("Multi-line strings are not supported"))
+		}
+		else if string.starts_with('{') {
+			Err(String::from("Inline tables are not supported"))
+		}
+		else {
+			let (string, rest) = Self::parse_string(string)?;
+			Self::end(rest)?;
+			Ok(Value::String(string))
+		}
+	}
+
+	/// Only a comment may follow a value
+	fn end(rest: &str) -> Result<(), String> {
+		let rest = rest.trim();
+		if rest.is_empty() || rest.starts_with('#') {
+			Ok(())
+		}
+		else {
+			Err(format!("Unexpected `{}` after value", rest))
+		}
+	}
+
+	/// Skips the whitespace, line breaks and comments between the items of an array
+	fn skip_blank(mut string: &str) -> &str {
+		loop {
+			string = string.trim_start();
+			match string.strip_prefix('#') {
+				Some(comment) => string = comment.split_once('\n').map(|(_, rest)| rest).unwrap_or(""),
+				None => return string,
+			}
+		}
+	}
+
+	/// Whether the array at the start of `string` has its `]`, brackets in strings and
+	/// comments aside
+	fn array_closed(string: &str) -> bool {
+		let mut quote = None;
+		let mut escaped = false;
+		let mut comment = false;
+		for c in string.chars().skip(1) {
+			match (quote, c) {
+				_ if comment => comment = c != '\n',
+				(Some(_), _) if escaped => escaped = false,
+				(Some('"'), '\\') => escaped = true,
+				(Some(q), c) if c == q => quote = None,
+				(Some(_), _) => (),
+				(None, '"' | '\'') => quote = Some(c),
+				(None, '#') => comment = true,
+				(None, ']') => return true,
+				(None, _) => (),
+			}
+		}
+		false
+	}
+
+	/// Reads a `"basic"` or `'literal'` string from the start, returns it and the rest
+	fn parse_string(string: &str) -> Result<(String, &str), String> {
+		let quote = match string.chars().next() {
+			Some(quote @ ('"' | '\'')) => quote,
+			_ => return Err(format!("Expected a quoted string, found `{}`", string)),
+		};
+		let mut result = String::new();
+		let mut escaped = false;
+		for (i, c) in string.char_indices().skip(1) {
+			if escaped {
+				result.push(match c {
+					'n' => '\n',
+					't' => '\t',
+					c => c,
+				});
+				escaped = false;
+			}
+			else if c == '\\' && quote == '"' {
+				escaped = true;
+			}
+			else if c == quote {
+				return Ok((result, &string[i + 1..]));
+			}
+			else {
+				result.push(c);
+			}
+		}
+		Err(String::from("Unclosed string"))
+	}
+
+	fn string(self, key: &str) -> Result<String, String> {
+		match self {
+			Value::String(string) => Ok(string),
+			Value::List(_) => Err(format!("`{}` expects a string, not an array", key)),
+		}
+	}
+
+	fn list(self) -> Vec<String> {
+		match self {
+			Value::String(string) => vec![string],
+			Value::List(list) => list,
+		}
+	}
+
+	fn variant<T: ValueEnum>(self, key: &str) -> Result<T, String> {
+		let string = self.string(key)?;
+		T::from_str(&string, true).map_err(|_| {
+			let possible: Vec<String> = T::value_variants().iter().map(value_name).collect();
+			format!("Invalid {} `{}`, possible values: {}", key, string, possible.join(", "))
+		})
+	}
+
+	/// Accepts both `30` and `"30"`
+	fn number(self, key: &str) -> Result<u64, String> {
+		let string = self.string(key)?;
+		string.parse().map_err(|_| format!("`{}` expects a number, found `{}`", key, string))
+	}
+
+	/// Accepts both `true` and `"true"`
+	fn boolean(self, key: &str) -> Result<bool, String> {
+		let string = self.string(key)?;
+		string.parse().map_err(|_| format!("`{}` expects true or false, found `{}`", key, string))
+	}
+
+	fn key_binding(self, key: &str) -> Result<KeyBinding, String> {
+		KeyBinding::parse(&self.string(key)?)
+	}
+}
+
+/// Where spcli keeps what it records: `$XDG_DATA_HOME/spcli`, falling back to `~/.local/share/spcli`
+/// (`%APPDATA%\spcli` on Windows)
+pub fn data_dir() -> Option<PathBuf> {
+	let dir = if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+		PathBuf::from(dir)
+	}
+	else if let Some(dir) = env::var_os("APPDATA").filter(|_| cfg!(windows)) {
+		PathBuf::from(dir)
+	}
+	else {
+		PathBuf::from(env::var_os("HOME")?).join(".local").join("share")
+	};
+	Some(dir.join("spcli"))
+}
+
+fn expand_home(path: &str) -> String {
+	match (path.strip_prefix("~/"), env::var("HOME")) {
+		(Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+		_ => path.to_string(),
+	}
+}
+
+fn value_name<T: ValueEnum>(value: &T) -> String {
+	value.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+}
+
+/// The effective settings of a session
+pub struct Settings {
+	pub config_path: Option<PathBuf>,
+	pub config_found: bool,
+	pub keymap: Setting<String>,
+	pub text: Setting<Vec<String>>,
+	pub convert: Setting<ConvertType>,
+	pub pinyin: Setting<ToneType>,
+	pub theme: Setting<ThemeType>,
+	pub layout: Setting<LayoutType>,
+	pub mode: Setting<Mode>,
+	pub lang: Setting<Locale>,
+	/// Seconds, 0 for never
+	pub idle_pause: Setting<u64>,
+	pub tone: Setting<bool>,
+	pub literal: Setting<bool>,
+	pub punctuation: Setting<bool>,
+	pub punctuation_map: Setting<String>,
+	/// Whether the timer stops while the keymap overlay is shown
+	pub overlay_pause: Setting<bool>,
+	/// Right answers in a row, 0 for never fading
+	pub fade: Setting<u64>,
+	/// Seconds, 0 for never
+	pub fade_delay: Setting<u64>,
+	pub word_spacing: Setting<bool>,
+	/// Whether each word is committed with space
+	pub commit: Setting<bool>,
+	pub quit: Setting<KeyBinding>,
+	pub restart: Setting<KeyBinding>,
+	pub pause: Setting<KeyBinding>,
+	pub key_map: Setting<KeyBinding>,
+}
+
+impl Settings {
+	pub fn resolve(config: Config, args: &Args) -> Self {
+		let default_bindings = KeyBindings::default();
+		Self {
+			config_path: config.path,
+			config_found: config.found,
+			keymap: Setting::pick(args.keymap.clone(), config.keymap, DEFAULT_KEYMAP.to_string()),
+			text: Setting::pick((!args.text.is_empty()).then(|| args.text.clone()), config.text, vec![DEFAULT_TEXT.to_string()]),
+			convert: Setting::pick(args.convert, config.convert, ConvertType::Off),
+			pinyin: Setting::pick(args.pinyin, config.pinyin, ToneType::Live),
+			theme: Setting::pick(args.theme, config.theme, ThemeType::Dark),
+			layout: Setting::pick(args.layout, config.layout, LayoutType::Qwerty),
+			mode: Setting::pick(args.mode, config.mode, Mode::Shuangpin),
+			lang: Setting::pick(args.lang, config.lang, Locale::detect()),
+			idle_pause: Setting::pick(args.idle_pause, config.idle_pause, DEFAULT_IDLE_PAUSE),
+			tone: Setting::pick(args.tone(), config.tone, false),
+			literal: Setting::pick(args.literal(), config.literal, false),
+			punctuation: Setting::pick(args.punctuation(), config.punctuation, false),
+			punctuation_map: Setting::pick(args.punctuation_map.clone(), config.punctuation_map, DEFAULT_PUNCTUATION.to_string()),
+			overlay_pause: Setting::pick(args.overlay_pause(), config.overlay_pause, false),
+			fade: Setting::pick(args.fade, config.fade, 0),
+			fade_delay: Setting::pick(args.fade_delay, config.fade_delay, DEFAULT_FADE_DELAY),
+			word_spacing: Setting::pick(args.word_spacing(), config.word_spacing, false),
+			commit: Setting::pick(args.commit(), config.commit, false),
+			quit: Setting::pick(None, config.quit, default_bindings.quit),
+			restart: Setting::pick(None, config.restart, default_bindings.restart),
+			pause: Setting::pick(None, config.pause, default_bindings.pause),
+			key_map: Setting::pick(None, config.key_map, default_bindings.key_map),
+		}
+	}
+
+	pub fn key_bindings(&self) -> KeyBindings {
+		KeyBindings {
+			quit: self.quit.value,
+			restart: self.restart.value,
+			pause: self.pause.value,
+			key_map: self.key_map.value,
+		}
+	}
+
+	pub fn options(&self) -> Options {
+		Options {
+			tone_on: self.pinyin.value,
+			theme: self.theme.value.theme(),
+			layout: self.layout.value,
+			key_bindings: self.key_bindings(),
+			locale: self.lang.value,
+			idle_pause: (self.idle_pause.value > 0).then(|| Duration::from_secs(self.idle_pause.value)),
+			overlay_pause: self.overlay_pause.value,
+			fade: (self.fade.value > 0).then(|| self.fade.value.try_into().unwrap_or(u32::MAX)),
+			fade_delay: (self.fade_delay.value > 0).then(|| Duration::from_secs(self.fade_delay.value)),
+			word_spacing: self.word_spacing.value,
+			rules: Rules {
+				mode: self.mode.value,
+				tone: self.tone.value,
+				literal: self.literal.value,
+				punctuation: None,
+				commit: self.commit.value,
+			},
+		}
+	}
+
+	/// The settings in the config file format, annotated with where each one comes from
+	pub fn show(&self) -> String {
+		let mut result = String::new();
+		match (&self.config_path, self.config_found) {
+			(Some(path), true) => writeln!(result, "# {}", path.display()).unwrap(),
+			(Some(path), false) => writeln!(result, "# {} (not found)", path.display()).unwrap(),
+			(None, _) => writeln!(result, "# no config directory").unwrap(),
+		}
+		let quoted = |string: &str| format!("{:?}", string);
+		let lines = [
+			("keymap", quoted(&self.keymap.value), self.keymap.source),
+			("text", format!("[{}]", self.text.value.iter().map(|t| quoted(t)).collect::<Vec<_>>().join(", ")), self.text.source),
+			("convert", quoted(&value_name(&self.convert.value)), self.convert.source),
+			("pinyin", quoted(&value_name(&self.pinyin.value)), self.pinyin.source),
+			("theme", quoted(&value_name(&self.theme.value)), self.theme.source),
+			("layout", quoted(&value_name(&self.layout.value)), self.layout.source),
+			("mode", quoted(&value_name(&self.mode.value)), self.mode.source),
+			("lang", quoted(&value_name(&self.lang.value)), self.lang.source),
+			("idle_pause", self.idle_pause.value.to_string(), self.idle_pause.source),
+			("tone", self.tone.value.to_string(), self.tone.source),
+			("literal", self.literal.value.to_string(), self.literal.source),
+			("punctuation", self.punctuation.value.to_string(), self.punctuation.source),
+			("punctuation_map", quoted(&self.punctuation_map.value), self.punctuation_map.source),
+			("overlay_pause", self.overlay_pause.value.to_string(), self.overlay_pause.source),
+			("fade", self.fade.value.to_string(), self.fade.source),
+			("fade_delay", self.fade_delay.value.to_string(), self.fade_delay.source),
+			("word_spacing", self.word_spacing.value.to_string(), self.word_spacing.source),
+			("commit", self.commit.value.to_string(), self.commit.source),
+		];
+		let binding_lines = [
+			("quit", quoted(&self.quit.value.to_string()), self.quit.source),
+			("restart", quoted(&self.restart.value.to_string()), self.restart.source),
+			("pause", quoted(&self.pause.value.to_string()), self.pause.source),
+			("keymap", quoted(&self.key_map.value.to_string()), self.key_map.source),
+		];
+		let width = lines.iter().chain(binding_lines.iter()).map(|(key, value, _)| key.len() + value.len() + 3).max().unwrap_or(0);
+		for (key, value, source) in lines {
+			writeln!(result, "{:<width$}  # {}", format!("{} = {}", key, value), source.describe(), width = width).unwrap();
+		}
+		writeln!(result, "\n[keybindings]").unwrap();
+		for (key, value, source) in binding_lines {
+			writeln!(result, "{:<width$}  # {}", format!("{} = {}", key, value), source.describe(), width = width).unwrap();
+		}
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use clap::Parser;
+
+	use super::*;
+
+	fn resolve(file: &str, args: &[&str]) -> Settings {
+		let config = Config::parse(file).unwrap();
+		let args = Args::parse_from(std::iter::once("spcli").chain(args.iter().copied()));
+		Settings::resolve(config, &args)
+	}
+
+	#[test]
+	fn flags_override_the_config_both_ways() {
+		let file = "tone = true\ncommit = true\n";
+		let settings = resolve(file, &[]);
+		assert!(settings.tone.value && settings.commit.value);
+		assert_eq!(settings.tone.source, Source::ConfigFile);
+
+		let settings = resolve(file, &["--no-tone", "--literal"]);
+		assert!(!settings.tone.value);
+		assert_eq!(settings.tone.source, Source::CommandLine);
+		assert!(settings.commit.value);
+		assert!(settings.literal.value);
+
+		let settings = resolve("", &["--word-spacing", "--no-word-spacing", "--no-commit", "--commit", "--overlay-pause", "--no-overlay-pause"]);
+		assert!(!settings.word_spacing.value && !settings.overlay_pause.value);
+		assert!(settings.commit.value);
+		assert!(!settings.punctuation.value);
+		assert_eq!(settings.punctuation.source, Source::Default);
+	}
+}
@@ -0,0 +1,94 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+/// 简体字与繁体字的对应, 格式见文件开头
+const VARIANTS: &str = include_str!("variants.txt");
+
+/// Which script the text is shown in
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ConvertType {
+	/// As written in the file
+	Off,
+	Simplified,
+	Traditional,
+}
+
+impl ConvertType {
+	pub fn convert(&self, string: &str) -> String {
+		match self {
+			ConvertType::Off => string.to_string(),
+			ConvertType::Simplified => string.chars().map(to_simplified).collect(),
+			ConvertType::Traditional => string.chars().map(to_traditional).collect(),
+		}
+	}
+}
+
+struct Tables {
+	to_simplified: HashMap<char, char>,
+	to_traditional: HashMap<char, char>,
+}
+
+fn tables() -> &'static Tables {
+	static TABLES: OnceLock<Tables> = OnceLock::new();
+	TABLES.get_or_init(|| {
+		let mut to_simplified = HashMap::new();
+		let mut to_traditional = HashMap::new();
+		for line in VARIANTS.lines() {
+			if line.trim().starts_with('#') {
+				continue;
+			}
+			for entry in line.split_whitespace() {
+				let mut chars = entry.chars();
+				let simplified = match chars.next() {
+					Some(c) => c,
+					None => continue,
+				};
+				for (i, traditional) in chars.enumerate() {
+					if i == 0 {
+						to_traditional.entry(simplified).or_insert(traditional);
+					}
+					if traditional != simplified {
+						to_simplified.entry(traditional).or_insert(simplified);
+					}
+				}
+			}
+		}
+		Tables { to_simplified, to_traditional }
+	})
+}
+
+/// The Simplified form of `c`, or `c` itself if it has none
+pub fn to_simplified(c: char) -> char {
+	tables().to_simplified.get(&c).copied().unwrap_or(c)
+}
+
+/// The Traditional form of `c`, or `c` itself if it has none
+///
+/// When `c` stands for several Traditional characters, the most common one is picked,
+/// eg. `发` becomes `發` even in `头发`.
+pub fn to_traditional(c: char) -> char {
+	tables().to_traditional.get(&c).copied().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn texts_convert_both_ways() {
+		let simplified = "学习汉语，写书。";
+		let traditional = ConvertType::Traditional.convert(simplified);
+		assert_eq!(traditional, "學習漢語，寫書。");
+		assert_eq!(ConvertType::Simplified.convert(&traditional), simplified);
+		assert_eq!(ConvertType::Off.convert(&traditional), traditional);
+	}
+
+	#[test]
+	fn the_most_common_traditional_form_is_picked() {
+		assert_eq!(ConvertType::Traditional.convert("头发和发现"), "頭發和發現");
+		assert_eq!(to_simplified('髮'), '发');
+		assert_eq!(to_simplified('發'), '发');
+		// Characters standing for themselves too, and those of no table
+		assert_eq!(to_traditional('丑'), '丑');
+		assert_eq!(to_traditional('a'), 'a');
+	}
+}
@@ -0,0 +1,180 @@
+use std::{io, time::{Duration, SystemTime, UNIX_EPOCH}, fmt::Write, collections::BTreeMap};
+
+use clap::ValueEnum;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{session::{Session, Mode}, store::Store};
+
+const FILE: &str = "history.tsv";
+
+/// A finished session, as kept in the history file
+pub struct Record {
+	/// Seconds since the Unix epoch
+	pub date: u64,
+	pub mode: Mode,
+	pub key_map: String,
+	pub chars: u32,
+	pub mistakes: u32,
+	pub typing: Duration,
+	pub reading: Duration,
+}
+
+impl Record {
+	pub fn new(session: &Session) -> Self {
+		let counter = session.counter();
+		Self {
+			date: SystemTime::now().duration_since(UNIX_EPOCH).map(|date| date.as_secs()).unwrap_or_default(),
+			mode: session.rules().mode,
+			key_map: counter.get_key_map_name(),
+			chars: counter.get_typed_words(),
+			mistakes: counter.get_mistakes(),
+			typing: counter.get_interval(),
+			reading: counter.get_reading_time(),
+		}
+	}
+
+	/// Characters per minute
+	pub fn speed(&self) -> f64 {
+		speed(self.chars, self.typing)
+	}
+
+	/// `date mode keymap chars mistakes typing reading`, separated by tabs, times in milliseconds
+	fn to_line(&self) -> String {
+		format!(
+			"{}\t{}\t{}\t{}\t{}\t{}\t{}",
+			self.date,
+			mode_name(self.mode),
+			self.key_map,
+			self.chars,
+			self.mistakes,
+			self.typing.as_millis(),
+			self.reading.as_millis(),
+		)
+	}
+
+	fn parse(line: &str) -> Option<Self> {
+		let fields: Vec<&str> = line.split('\t').collect();
+		if fields.len() != 7 {
+			return None;
+		}
+		Some(Self {
+			date: fields[0].parse().ok()?,
+			mode: Mode::from_str(fields[1], true).ok()?,
+			key_map: fields[2].to_string(),
+			chars: fields[3].parse().ok()?,
+			mistakes: fields[4].parse().ok()?,
+			typing: Duration::from_millis(fields[5].parse().ok()?),
+			reading: Duration::from_millis(fields[6].parse().ok()?),
+		})
+	}
+}
+
+/// Adds `record` to `history.tsv`
+pub fn append(record: &Record) -> Result<(), io::Error> {
+	Store::data().append(FILE, &record.to_line())
+}
+
+/// Every record in `history.tsv`, broken lines skipped
+pub fn load() -> Result<Vec<Record>, io::Error> {
+	Ok(Store::data().read(FILE)?.lines().filter_map(Record::parse).collect())
+}
+
+/// One row per mode and keymap, so that full pinyin and shuangpin can be compared
+pub fn summary(records: &[Record]) -> String {
+	let mut groups: BTreeMap<(String, String), Vec<&Record>> = BTreeMap::new();
+	for record in records {
+		groups.entry((mode_name(record.mode), record.key_map.clone())).or_default().push(record);
+	}
+	let mut rows = vec![[
+		String::from("mode"),
+		String::from("keymap"),
+		String::from("sessions"),
+		String::from("chars"),
+		String::from("speed"),
+		String::from("best"),
+		String::from("mistakes"),
+	]];
+	for ((mode, key_map), records) in groups {
+		let chars: u32 = records.iter().map(|record| record.chars).sum();
+		let typing: Duration = records.iter().map(|record| record.typing).sum();
+		let mistakes: u32 = records.iter().map(|record| record.mistakes).sum();
+		let best = records.iter().map(|record| record.speed()).fold(0f64, f64::max);
+		rows.push([
+			mode,
+			key_map,
+			records.len().to_string(),
+			chars.to_string(),
+			format!("{:.1}/min", speed(chars, typing)),
+			format!("{:.1}/min", best),
+			format!("{:.1}%", if chars == 0 { 0f64 } else { mistakes as f64 / chars as f64 * 100f64 }),
+		]);
+	}
+	let widths: Vec<usize> = (0..7).map(|col| rows.iter().map(|row| row[col].width()).max().unwrap_or(0)).collect();
+	let mut result = String::new();
+	for row in rows {
+		let cells: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| {
+			format!("{}{}", cell, " ".repeat(width - cell.width()))
+		}).collect();
+		writeln!(result, "{}", cells.join("  ").trim_end()).unwrap();
+	}
+	result
+}
+
+fn speed(chars: u32, typing: Duration) -> f64 {
+	if typing.is_zero() {
+		0f64
+	}
+	else {
+		chars as f64 * 60f64 / typing.as_secs_f64()
+	}
+}
+
+fn mode_name(mode: Mode) -> String {
+	mode.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record(mode: Mode, key_map: &str, chars: u32, mistakes: u32, typing: u64) -> Record {
+		Record {
+			date: 1700000000,
+			mode,
+			key_map: key_map.to_string(),
+			chars,
+			mistakes,
+			typing: Duration::from_secs(typing),
+			reading: Duration::from_millis(1500),
+		}
+	}
+
+	#[test]
+	fn records_read_back_their_lines() {
+		let line = record(Mode::Quanpin, "微软双拼", 120, 3, 60).to_line();
+		assert_eq!(line, "1700000000\tquanpin\t微软双拼\t120\t3\t60000\t1500");
+		let read = Record::parse(&line).unwrap();
+		assert_eq!(read.to_line(), line);
+		assert_eq!(read.mode, Mode::Quanpin);
+		assert!(Record::parse("1700000000\tquanpin\t微软双拼\t120").is_none());
+		assert!(Record::parse("x\tquanpin\t微软双拼\t120\t3\t60000\t1500").is_none());
+	}
+
+	#[test]
+	fn summary_has_a_row_per_mode_and_keymap() {
+		let records = [
+			record(Mode::Shuangpin, "小鹤双拼", 100, 2, 60),
+			record(Mode::Shuangpin, "小鹤双拼", 100, 0, 30),
+			record(Mode::Quanpin, "小鹤双拼", 60, 6, 60),
+			record(Mode::Shuangpin, "自然码", 50, 0, 60),
+		];
+		let summary = summary(&records);
+		let lines: Vec<&str> = summary.lines().collect();
+		assert_eq!(lines.len(), 4);
+		assert!(lines[0].starts_with("mode"));
+		let cells = |line: &str| line.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+		assert_eq!(cells(lines[1]), ["quanpin", "小鹤双拼", "1", "60", "60.0/min", "60.0/min", "10.0%"]);
+		assert_eq!(cells(lines[2]), ["shuangpin", "小鹤双拼", "2", "200", "133.3/min", "200.0/min", "1.0%"]);
+		assert_eq!(cells(lines[3]), ["shuangpin", "自然码", "1", "50", "50.0/min", "50.0/min", "0.0%"]);
+	}
+}
@@ -0,0 +1,111 @@
+use std::env;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Locale {
+	ZhCn,
+	En,
+}
+
+impl Locale {
+	/// Picks the locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order.
+	/// Falls back to Chinese when none of them is set.
+	pub fn detect() -> Self {
+		let lang = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+			.filter_map(|name| env::var(name).ok())
+			.find(|value| !value.is_empty());
+		match lang {
+			Some(lang) if lang.starts_with("zh") => Locale::ZhCn,
+			Some(_) => Locale::En,
+			None => Locale::ZhCn,
+		}
+	}
+
+	pub fn messages(&self) -> &'static Messages {
+		match self {
+			Locale::ZhCn => &ZH_CN,
+			Locale::En => &EN,
+		}
+	}
+}
+
+/// Every string shown in the UI
+///
+/// `{quit}`, `{restart}`, `{pause}` and `{keymap}` in `key_hint` are replaced by the key bindings
+#[derive(Debug)]
+pub struct Messages {
+	pub mistake_label: &'static str,
+	pub tone_mistake_label: &'static str,
+	pub speed_label: &'static str,
+	pub time_label: &'static str,
+	pub reading_label: &'static str,
+	/// Followed by the words typed, then `clean_label` and those typed without a mistake
+	pub word_label: &'static str,
+	pub clean_label: &'static str,
+	/// Shown instead of the time until the first key
+	pub ready: &'static str,
+	/// Follows a count of characters, eg. `12字`
+	pub char_unit: &'static str,
+	/// Follows a speed, eg. `30字/min`
+	pub speed_unit: &'static str,
+	pub finished: &'static str,
+	pub paused: &'static str,
+	pub key_hint: &'static str,
+	/// Title of the keymap overlay, followed by the name of the scheme
+	pub key_map_title: &'static str,
+	pub initials_label: &'static str,
+	pub finals_label: &'static str,
+	pub zero_initial_label: &'static str,
+	pub key_map_close: &'static str,
+	/// Said by `spcli review` when no syllable is due
+	pub nothing_due: &'static str,
+}
+
+impl Messages {
+	pub fn key_hint(&self, quit: &str, restart: &str, pause: &str, key_map: &str) -> String {
+		self.key_hint.replace("{quit}", quit).replace("{restart}", restart).replace("{pause}", pause).replace("{keymap}", key_map)
+	}
+}
+
+pub const ZH_CN: Messages = Messages {
+	mistake_label: "错误: ",
+	tone_mistake_label: "声调错误: ",
+	speed_label: "速度: ",
+	time_label: "耗时: ",
+	reading_label: "阅读: ",
+	word_label: "词语: ",
+	clean_label: ", 无误 ",
+	ready: "准备",
+	char_unit: "字",
+	speed_unit: "字/min",
+	finished: "🎉🎉 完成 🎉🎉",
+	paused: "已暂停, 按任意键继续",
+	key_hint: "按 <{quit}> 退出, <{restart}> 重来, <{pause}> 暂停, <{keymap}> 键位表",
+	key_map_title: "键位表: ",
+	initials_label: "声母",
+	finals_label: "韵母",
+	zero_initial_label: "零声母",
+	key_map_close: "按任意键返回",
+	nothing_due: "今天没有需要复习的音节",
+};
+
+pub const EN: Messages = Messages {
+	mistake_label: "Mistakes: ",
+	tone_mistake_label: "Tone mistakes: ",
+	speed_label: "Speed: ",
+	time_label: "Time: ",
+	reading_label: "Reading: ",
+	word_label: "Words: ",
+	clean_label: ", clean ",
+	ready: "Ready",
+	char_unit: " chars",
+	speed_unit: " chars/min",
+	finished: "🎉🎉 Finished 🎉🎉",
+	paused: "Paused, press any key to resume",
+	key_hint: "Press <{quit}> to quit, <{restart}> to try again, <{pause}> to pause, <{keymap}> for the keymap",
+	key_map_title: "Keymap: ",
+	initials_label: "Initials",
+	finals_label: "Finals",
+	zero_initial_label: "Without initial",
+	key_map_close: "Press any key to go back",
+	nothing_due: "Nothing to review today",
+};
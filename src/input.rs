@@ -1,44 +1,121 @@
-use std::{io, time::{Duration, Instant}, fs, collections::HashMap};
-
-use crossterm::{terminal, event, style::Color};
+use std::{io, time::{Duration, Instant}, collections::HashMap, fmt, process::exit};
+
+use crossterm::{terminal, event, cursor, execute};
+
+use crate::{pin::{Document, Passage}, keymap::KeyMap, session::{Session, Outcome, Rules}, render::{self, Render, ToneType, Keyboard, LayoutType}, theme::Theme, i18n::Locale, backend::{Backend, Terminal}, history, mastery::Mastery, review::{self, Schedule}};
+
+/// How a session looks and behaves
+pub struct Options {
+	pub tone_on: ToneType,
+	pub theme: Theme,
+	pub layout: LayoutType,
+	pub key_bindings: KeyBindings,
+	pub locale: Locale,
+	/// Pause after this long without a key press
+	pub idle_pause: Option<Duration>,
+	/// Stop the timer while the keymap overlay is shown
+	pub overlay_pause: bool,
+	/// Hide the pinyin and the next keys of a syllable typed right this many times in a row
+	pub fade: Option<u32>,
+	/// Show a hidden hint again after this long without a key press
+	pub fade_delay: Option<Duration>,
+	/// Leave a blank column between the words of the passage
+	pub word_spacing: bool,
+	pub rules: Rules,
+}
 
-use crate::{die, pin::{Document, CharStatus}, render::{Render, ToneType, Keyboard}};
-pub struct Input<'b> {
-	document: &'b mut Document,
-	active_passage: usize,
-	render: Render,
-	passed: usize,
-	typing_consonant: bool,
+/// Runs a `Session` in the terminal
+pub struct Input<'b, B: Backend = Terminal> {
+	session: Session<'b>,
+	render: Render<B>,
+	keyboard: Keyboard,
 	key_map: &'b KeyMap,
-	keyboard: Keyboard<'b>,
-	counter: Counter,
+	/// The keymap overlay is shown over the passage
+	overlay: bool,
+	overlay_pause: bool,
+	fade: Option<u32>,
+	fade_delay: Option<Duration>,
+	/// Streaks of the syllables, `None` unless given by `with_mastery`
+	mastery: Option<Mastery>,
+	/// The hint of the character being typed came back, after a mistake or `fade_delay`
+	revealed: bool,
+	/// `None` unless given by `with_schedule`
+	schedule: Option<Schedule>,
+	/// Mistakes on the character being typed
+	slips: u32,
 	stopped: bool,
 	restart: bool,
-	end: bool,
+	quit: bool,
 	tone_on: ToneType,
+	word_spacing: bool,
+	key_bindings: KeyBindings,
+	idle_pause: Option<Duration>,
+	last_input: Instant,
+	recorded: bool,
 }
 
 impl<'b> Input<'b> {
-	pub fn new(document: &'b mut Document, key_map: &'b KeyMap, tone_on: ToneType) -> Self {
-		let rnd = document.get_random();
-		let passage = document.passages.get(rnd).unwrap();
-		let total_words = passage.chars.iter().fold(0, |acc, char| { if !char.is_mark { acc + 1 } else { acc } });
+	pub fn new(document: &Document, key_map: &'b KeyMap, options: &Options) -> Self {
+		Self::with_backend(document, key_map, options, Terminal::new())
+	}
+}
+
+impl<'b, B: Backend> Input<'b, B> {
+	pub fn with_backend(document: &Document, key_map: &'b KeyMap, options: &Options, backend: B) -> Self {
+		let passage = document.passages.get(document.get_random()).unwrap().clone();
+		Self::with_passage(passage, key_map, options, backend)
+	}
+
+	pub fn with_passage(passage: Passage, key_map: &'b KeyMap, options: &Options, backend: B) -> Self {
+		let session = Session::new(passage, key_map, options.rules.clone());
 		Self {
-    		active_passage: rnd,
-			document,
-			render: Render::new(),
-			passed: 0,
-			typing_consonant: true,
-			keyboard: Keyboard::default(key_map),
+			session,
+			render: Render::new(
+				backend,
+				options.theme,
+				options.locale.messages(),
+				options.locale.messages().key_hint(
+					&options.key_bindings.quit.to_string(),
+					&options.key_bindings.restart.to_string(),
+					&options.key_bindings.pause.to_string(),
+					&options.key_bindings.key_map.to_string(),
+				),
+			),
+			keyboard: Keyboard::new(key_map, options.layout.rows()),
 			key_map,
-			counter: Counter::new(total_words, key_map.name.clone()),
+			overlay: false,
+			overlay_pause: options.overlay_pause,
+			fade: options.fade,
+			fade_delay: options.fade_delay,
+			mastery: None,
+			revealed: false,
+			schedule: None,
+			slips: 0,
 			stopped: false,
 			restart: false,
-			end: false,
-			tone_on,
+			quit: false,
+			tone_on: options.tone_on,
+			word_spacing: options.word_spacing,
+			key_bindings: options.key_bindings,
+			idle_pause: options.idle_pause,
+			last_input: Instant::now(),
+			recorded: false,
 		}
 	}
 
+	/// Fades the hints of the syllables `mastery` knows well, with `Options::fade`, and
+	/// keeps it up to date. Saved when `run` is over.
+	pub fn with_mastery(mut self, mastery: Mastery) -> Self {
+		self.mastery = Some(mastery);
+		self
+	}
+
+	/// Grades every syllable typed in `schedule`, for a review. Saved when `run` is over.
+	pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+		self.schedule = Some(schedule);
+		self
+	}
+
 	pub fn run(&mut self) -> bool {
 		// Enable Raw Mode
 		if !terminal::is_raw_mode_enabled().expect("Can not read if raw mode is enabled") {
@@ -46,57 +123,244 @@ impl<'b> Input<'b> {
 		}
 
 		// init
-		self.render.refresh().unwrap();
-		let passage = self.document.passages.get(self.active_passage).unwrap();
-		self.render.render_passage(passage, self.passed, self.tone_on).unwrap();
-		self.render.render_keyboard(&self.keyboard, HashMap::new()).unwrap();
-		self.render.render_counter(&self.counter).unwrap();
+		self.render_all().unwrap();
 
 		while !self.stopped {
-			if event::poll(Duration::from_millis(1000)).unwrap() {
-				// It's guaranteed that `read_input` won't block
-				if let Err(err) = self.read_input() {
-					eprintln!("{}", err);
-					die();
-					break;
-				}
-			}
-			else if !self.stopped {
-				self.render.render_counter(&self.counter).unwrap();
+			if let Err(err) = self.step() {
+				eprintln!("{}", err);
+				// Quit once the progress is saved
+				self.quit = true;
+				break;
 			}
 		}
+		// Like the history, the mastery and the schedule never stop the practice
+		if let Some(mastery) = self.mastery.as_ref() {
+			let _ = mastery.save();
+		}
+		if let Some(schedule) = self.schedule.as_ref() {
+			let _ = schedule.save();
+		}
+		if self.quit {
+			die();
+		}
 		self.restart
 
 	}
 
+	/// Handles a key, or the time passing if none comes within a second
+	fn step(&mut self) -> Result<(), io::Error> {
+		if event::poll(Duration::from_millis(1000))? {
+			// It's guaranteed that `read_input` won't block
+			self.read_input()?;
+			self.record();
+		}
+		else if !self.stopped {
+			self.check_idle()?;
+			self.check_fade_delay()?;
+			self.render.render_counter(self.session.counter())?;
+			self.render.present()?;
+		}
+		Ok(())
+	}
+
+	pub fn session(&self) -> &Session<'b> {
+		&self.session
+	}
+
+	pub fn backend(&self) -> &B {
+		self.render.backend()
+	}
+
+	/// Whether the session is over, by finishing, quitting or restarting
+	pub fn is_stopped(&self) -> bool {
+		self.stopped
+	}
+
+	pub fn is_restart(&self) -> bool {
+		self.restart
+	}
+
+	pub fn is_quit(&self) -> bool {
+		self.quit
+	}
+
+	/// Draws the whole screen
+	pub fn render_all(&mut self) -> Result<(), io::Error> {
+		self.redraw()?;
+		self.render.render_counter(self.session.counter())?;
+		self.render.present()?;
+		Ok(())
+	}
+
+	/// Draws everything but the status bar, without any highlight
+	fn redraw(&mut self) -> Result<(), io::Error> {
+		self.render.refresh()?;
+		if self.session.is_end() {
+			self.render.render_summary(self.session.counter())?;
+		}
+		else if self.overlay {
+			self.render.render_key_map(self.key_map, &self.keyboard)?;
+		}
+		else if self.session.is_paused() {
+			self.render.render_paused()?;
+			self.render.render_keyboard(&self.keyboard, HashMap::new())?;
+		}
+		else {
+			self.render.render_passage(self.session.passage(), self.session.passed(), self.tone_on, self.session.rules().tone, self.hint_hidden(), self.word_spacing)?;
+			self.render.render_keyboard(&self.keyboard, HashMap::new())?;
+		}
+		Ok(())
+	}
+
+	/// Whether the pinyin and the next keys of the character being typed are hidden, as its syllable is mastered
+	fn hint_hidden(&self) -> bool {
+		match (self.fade, self.mastery.as_ref(), self.session.typing().and_then(|c| c.pinyin.as_ref())) {
+			(Some(fade), Some(mastery), Some(pinyin)) => !self.revealed && mastery.streak(&pinyin.pinyin) >= fade,
+			_ => false,
+		}
+	}
+
+	/// Shows the hidden hint again if nothing has been pressed for `fade_delay`
+	fn check_fade_delay(&mut self) -> Result<(), io::Error> {
+		if let Some(fade_delay) = self.fade_delay {
+			if self.hint_hidden() && !self.overlay && !self.session.is_paused() && self.last_input.elapsed() >= fade_delay {
+				self.revealed = true;
+				self.render_typing(None)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// A mistake resets the streak of the syllable and shows its hint again. Typing it
+	/// without the hint coming back makes the streak longer.
+	fn update_mastery(&mut self, syllable: Option<String>, outcome: Outcome) {
+		let passed = matches!(outcome, Outcome::Passed | Outcome::Finished);
+		if let (Some(mastery), Some(syllable)) = (self.mastery.as_mut(), syllable) {
+			if passed && !self.revealed {
+				mastery.pass(&syllable);
+			}
+			else if !passed && !outcome.is_correct() && !matches!(outcome, Outcome::Ignored | Outcome::WrongCommit) {
+				mastery.fail(&syllable);
+				self.revealed = true;
+			}
+		}
+		if passed {
+			self.revealed = false;
+		}
+	}
+
+	/// Counts the mistakes on the character being typed, and grades its syllable once it is
+	/// passed. A missed commit is no mistake on the syllable.
+	fn update_schedule(&mut self, syllable: Option<String>, outcome: Outcome) {
+		match outcome {
+			Outcome::Passed | Outcome::Finished => {
+				if let (Some(schedule), Some(syllable)) = (self.schedule.as_mut(), syllable) {
+					schedule.grade(&syllable, review::quality(self.slips), review::today());
+				}
+				self.slips = 0;
+			},
+			Outcome::HalfTyped | Outcome::WrongCommit | Outcome::Ignored => (),
+			_ => self.slips += 1,
+		}
+	}
+
+	/// Adds a finished session to the history, once. Reviews are left out, their passages
+	/// being made of the syllables due rather than of the text.
+	fn record(&mut self) {
+		if self.session.is_end() && self.session.counter().is_started() && !self.recorded && self.schedule.is_none() {
+			self.recorded = true;
+			// The history is nice to have, it never stops the practice
+			let _ = history::append(&history::Record::new(&self.session));
+		}
+	}
+
+	/// Pauses if nothing has been pressed for `idle_pause` since the first key
+	fn check_idle(&mut self) -> Result<(), io::Error> {
+		if let Some(idle_pause) = self.idle_pause {
+			let started = self.session.counter().is_started();
+			if started && !self.overlay && !self.session.is_end() && !self.session.is_paused() && self.last_input.elapsed() >= idle_pause {
+				self.session.pause_since(self.last_input);
+				self.redraw()?;
+			}
+		}
+		Ok(())
+	}
+
 	pub fn read_input(&mut self) -> Result<(), io::Error> {
 		let input = event::read()?;
+		self.handle_event(input)
+	}
+
+	pub fn handle_event(&mut self, input: event::Event) -> Result<(), io::Error> {
 		match input {
 			event::Event::Key(key) => {
 				self.process_key_event(key)?;
 			},
 			event::Event::Resize(_, _) => {
 				self.render.update_passage_rect();
-				self.render.refresh()?;
-				let passage = self.document.passages.get(self.active_passage).unwrap();
-				self.render.render_passage(passage, self.passed, self.tone_on)?;
-				self.render.render_keyboard(&self.keyboard, HashMap::new())?;
+				self.redraw()?;
 			},
 			_ => ()
 		}
 
-		self.render.render_counter(&self.counter)?;
+		self.render.render_counter(self.session.counter())?;
+		self.render.present()?;
 		Ok(())
 	}
 
 	fn process_key_event(&mut self, event: event::KeyEvent) -> Result<(), io::Error> {
+		if self.key_bindings.quit.matches(&event) {
+			self.stopped = true;
+			self.quit = true;
+			return Ok(());
+		}
+		if self.key_bindings.restart.matches(&event) {
+			self.stopped = true;
+			self.restart = true;
+			return Ok(());
+		}
+		self.last_input = Instant::now();
+		if self.overlay {
+			// The key only closes the overlay, and resumes if it paused
+			self.overlay = false;
+			if self.overlay_pause {
+				self.session.resume();
+			}
+			return self.redraw();
+		}
+		if self.session.is_paused() {
+			// The key only resumes
+			self.session.resume();
+			return self.redraw();
+		}
+		if self.key_bindings.pause.matches(&event) {
+			if !self.session.is_end() {
+				self.session.pause();
+				self.redraw()?;
+			}
+			return Ok(());
+		}
+		if self.key_bindings.key_map.matches(&event) {
+			if !self.session.is_end() {
+				self.overlay = true;
+				if self.overlay_pause && self.session.counter().is_started() {
+					self.session.pause();
+				}
+				self.redraw()?;
+			}
+			return Ok(());
+		}
 		match event.modifiers {
-			event::KeyModifiers::CONTROL => self.process_ctrl_key_event(event),
-			event::KeyModifiers::NONE => {
-				if self.end {
+			// Shift for capital letters and the symbols above the number and punctuation keys
+			event::KeyModifiers::NONE | event::KeyModifiers::SHIFT => {
+				if self.session.is_end() {
 					return Ok(()); 
 				}
-				if let event::KeyCode::Char(character) = event.code {
+				if let event::KeyCode::Char(mut character) = event.code {
+					// Some terminals report the key under shift rather than what it types
+					if event.modifiers == event::KeyModifiers::SHIFT {
+						character = render::shifted(character).unwrap_or(character);
+					}
 					self.check_input(character)?;
 				}
 				Ok(())
@@ -105,252 +369,148 @@ impl<'b> Input<'b> {
 		}
 	}
 
-	fn process_ctrl_key_event(&mut self, event: event::KeyEvent) -> Result<(), io::Error> {
-		match event.code {
-			event::KeyCode::Char('q') => {
-				die();
-			},
-			event::KeyCode::Char('r') => {
-				self.stopped = true;
-				self.restart = true;
-			},
-			_ => ()
+	fn check_input(&mut self, character: char) -> Result<(), io::Error> {
+		let syllable = self.session.typing().and_then(|c| c.pinyin.as_ref()).map(|pinyin| pinyin.pinyin.clone());
+		let outcome = self.session.feed(character);
+		self.update_mastery(syllable.clone(), outcome);
+		self.update_schedule(syllable, outcome);
+		if outcome == Outcome::Finished {
+			self.render.render_summary(self.session.counter())?;
+			return Ok(());
 		}
-		Ok(())
+		self.render_typing(Some((character, outcome.is_correct())))
 	}
 
-	fn check_input(&mut self, character: char) -> Result<(), io::Error> {
-		let passage = self.document.passages.get_mut(self.active_passage).unwrap();
-		// Turn the color of the auto-passed mark to green;
-		while passage.chars.get(self.passed + 1).unwrap().is_mark {
-			passage.chars.get_mut(self.passed + 1).unwrap().set_status(CharStatus::Passed);
-			self.passed += 1;
-		}
-
-		let typing = passage.chars.get_mut(self.passed + 1).unwrap();
-		let pinyin = &typing.pinyin.as_ref().unwrap().pinyin_splitted;
-		let map = &self.key_map.map;
-		// Check if the key is correct
-		let pass = 
-			if self.typing_consonant {
-				let expected = &pinyin[0];
-				if pinyin[0].is_empty() && pinyin[1] == "er" && self.key_map.split_er && character != self.key_map.leader{
-					// 处理特殊的"er"
-					map.get("e").unwrap().contains(&character)
-				}
-				else if expected.is_empty() {
-					// 零声母
-					match self.key_map.leader {
-						'*' => map.get(&pinyin[1][0..1]).unwrap().contains(&character),
-						c => character == c,
-					}
-				}
-				else {
-					map.get(expected).unwrap().contains(&character)
-				}
-			}
-			else {
-				let expected = &pinyin[1];
-				if pinyin[0].is_empty() && pinyin[1] == "er" && self.key_map.split_er && character != self.key_map.leader{
-					map.get("r").unwrap().contains(&character)
-				}
-				else {
-					map.get(expected).unwrap().contains(&character)
-				}
-			};
-
-		if self.typing_consonant {
-			if pass {
-				typing.set_status(CharStatus::TypingHalf);
-				self.typing_consonant = false;
-			}
-			else {
-				typing.set_status(CharStatus::ErrorConsonant);
-				self.counter.add_mistake();
-			}
+	/// Draws the passage and the keyboard, with the `pressed` key and whether it was right,
+	/// and the next keys unless the hint is hidden
+	fn render_typing(&mut self, pressed: Option<(char, bool)>) -> Result<(), io::Error> {
+		let hint_hidden = self.hint_hidden();
+		let theme = self.render.theme();
+		let mut highlight_rule = HashMap::new();
+		if let Some((key, correct)) = pressed {
+			highlight_rule.insert(key, if correct {theme.highlight_pass} else {theme.highlight_error});
 		}
-		else if pass {
-			typing.set_status(CharStatus::Passed);
-			self.passed += 1;
-			self.counter.add_typed_words();
-			if self.passed >= passage.chars.len() - 2 {
-				// END
-				self.end = true;
-				self.counter.lock();
-				self.render.render_summary(&self.counter)?;
-				return Ok(());
-			}
-			else {
-				loop {
-					if let Some(character) = passage.chars.get(self.passed + 1) {
-						if character.is_mark {
-							passage.chars.get_mut(self.passed + 1).unwrap().set_status(CharStatus::Passed);
-							self.passed += 1;
-						}
-						else {
-							break;
-						}
-					}
-					else {
-						// END
-						self.counter.lock();
-						self.end = true;
-						self.render.render_summary(&self.counter)?;
-						return Ok(());
-					}
-				}
-			}
-			self.typing_consonant = true;
-			passage.chars.get_mut(self.passed + 1).unwrap().set_status(CharStatus::Typing);
-		}
-		else {
-			typing.set_status(CharStatus::ErrorVowel);
-			self.counter.add_mistake();
-		}
-		
-
-
-		// Rerender thr keyboard
-		let next = {
-			let typing = passage.chars.get(self.passed+1).unwrap();
-			let pinyin = &typing.pinyin.as_ref().unwrap().pinyin_splitted;
-			let mut phoneme = pinyin[if self.typing_consonant {0} else {1}].clone();
-			if phoneme.is_empty() {
-				phoneme = match self.key_map.leader {
-					'*' => {
-						let phoneme = &pinyin[1][0..1];
-						phoneme.to_string()
-					},
-					c => String::from(c),
-				};
-			}
-			map.get(&phoneme)
-		};
-
-		let mut highlight_rule = HashMap::from([
-			(character.to_string(), if pass {Color::DarkGreen} else {Color::DarkRed}),
-		]);
-
-		if let Some(keys) = next {
-			for key in keys {
-				highlight_rule.insert(key.to_string(), Color::DarkBlue);
+		if !hint_hidden {
+			for key in self.session.next_keys() {
+				highlight_rule.insert(key, theme.highlight);
 			}
 		}
 
 		self.render.refresh()?;
-		self.render.render_passage(passage, self.passed, self.tone_on)?;
+		self.render.render_passage(self.session.passage(), self.session.passed(), self.tone_on, self.session.rules().tone, hint_hidden, self.word_spacing)?;
 		self.render.render_keyboard(&self.keyboard, highlight_rule)?;
 
 		Ok(())
 	}
 }
 
-pub struct KeyMap {
-	leader: char,
-	name: String,
-	pub map: HashMap<String, Vec<char>>,
-	split_er: bool,
+/// Leaves raw mode and exits
+pub fn die() {
+	leave_raw_mode();
+	exit(0);
 }
 
-impl KeyMap {
-	pub fn open(filepath: &str) -> Result<KeyMap, io::Error> {
-		let file = fs::read_to_string(filepath)?;
-		let mut map = HashMap::new();
-		let mut leader = 'o';
-		let mut split_er = true;
-		let mut name:String = String::new();
-		for line in file.split('\n') {
-			if line.trim().starts_with('#') || line.trim().is_empty() {
-				continue;
-			}
-			let split: Vec<&str> = line.split(&[':'][..]).collect();
-			let phoneme = split[0].trim().to_owned();
-			let key:Vec<char> = split[1].trim().chars().collect();
+/// Leaves raw mode and clears what the sessions drew, for the shell to take over
+pub fn leave() -> Result<(), io::Error> {
+	leave_raw_mode();
+	execute!(io::stdout(), terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))
+}
 
-			if phoneme == "leader" {
-				leader = key[0];
-			}
-			else if phoneme == "leader_er" {
-				map.insert("er".to_owned(), vec![key[0]]);
-			}
-			else if phoneme == "split_er" {
-				split_er = key[0] == '1';
-			}
-			else if phoneme == "name" {
-				name = split[1].trim().to_string();
-			}
-			else {
-				map.insert(phoneme, key);
-			}
-		}
-		Ok(Self {
-			leader,
-			map,
-			split_er,
-			name
-		})
+fn leave_raw_mode() {
+	if terminal::is_raw_mode_enabled().expect("Can not read if raw mode is enabled") {
+		terminal::disable_raw_mode().expect("Failed to disable raw mode");
 	}
 }
 
-pub struct Counter {
-	total_words: u32,
-	typed_words: u32,
-	mistakes: u32,
-	start_time: Instant,
-	time_locked: bool, 
-	end_time: Instant,
-	key_map_name: String,
+/// A key combination such as `C-q`, bound to an action of the session
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct KeyBinding {
+	pub code: event::KeyCode,
+	pub modifiers: event::KeyModifiers,
 }
 
-impl Counter {
-	pub fn new(total_words: u32, key_map_name: String) -> Self {
-		Self {
-			total_words,
-			typed_words: 0,
-			mistakes: 0,
-			start_time: Instant::now(),
-			end_time: Instant::now(),
-			time_locked: false,
-			key_map_name,
-		}
-	}
-
-	pub fn add_typed_words(&mut self) {
-		self.typed_words += 1;
-	}
-
-	pub fn add_mistake(&mut self) {
-		self.mistakes += 1;
-	}
-
-	pub fn get_total_words(&self) -> u32 {
-		self.total_words
+impl KeyBinding {
+	pub fn new(code: event::KeyCode, modifiers: event::KeyModifiers) -> Self {
+		Self { code, modifiers }
 	}
 
-	pub fn get_typed_words(&self) -> u32 {
-		self.typed_words
-	}
-
-	pub fn get_mistakes(&self) -> u32 {
-		self.mistakes
+	/// Parses the emacs-like notation: `C-` Ctrl, `M-` Alt, `S-` Shift, followed by
+	/// a single character or one of `Esc` `Enter` `Tab` `Space` `Backspace` `F1`..`F12`
+	/// eg. `C-q`, `M-S-p`, `F5`
+	pub fn parse(string: &str) -> Result<Self, String> {
+		let mut modifiers = event::KeyModifiers::NONE;
+		let mut rest = string.trim();
+		while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+			modifiers |= match &rest[0..1] {
+				"C" => event::KeyModifiers::CONTROL,
+				"M" => event::KeyModifiers::ALT,
+				"S" => event::KeyModifiers::SHIFT,
+				m => return Err(format!("Unknown modifier `{}` in `{}`", m, string)),
+			};
+			rest = &rest[2..];
+		}
+		let code = match rest {
+			"Esc"		=> event::KeyCode::Esc,
+			"Enter"		=> event::KeyCode::Enter,
+			"Tab"		=> event::KeyCode::Tab,
+			"Space"		=> event::KeyCode::Char(' '),
+			"Backspace"	=> event::KeyCode::Backspace,
+			f if f.len() > 1 && f.starts_with('F') => {
+				match f[1..].parse::<u8>() {
+					Ok(n) if (1..=12).contains(&n) => event::KeyCode::F(n),
+					_ => return Err(format!("Unknown key `{}` in `{}`", f, string)),
+				}
+			},
+			c if c.chars().count() == 1 => event::KeyCode::Char(c.chars().next().unwrap()),
+			k => return Err(format!("Unknown key `{}` in `{}`", k, string)),
+		};
+		Ok(Self::new(code, modifiers))
 	}
 
-	pub fn get_key_map_name(&self) -> String {
-		self.key_map_name.clone()
+	pub fn matches(&self, event: &event::KeyEvent) -> bool {
+		self.code == event.code && self.modifiers == event.modifiers
 	}
+}
 
-	pub fn get_interval(&self) -> Duration {
-		if self.time_locked {
-			self.end_time.duration_since(self.start_time)
+impl fmt::Display for KeyBinding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.modifiers.contains(event::KeyModifiers::CONTROL) {
+			write!(f, "C-")?;
 		}
-		else {
-			Instant::now().duration_since(self.start_time)
+		if self.modifiers.contains(event::KeyModifiers::ALT) {
+			write!(f, "M-")?;
+		}
+		if self.modifiers.contains(event::KeyModifiers::SHIFT) {
+			write!(f, "S-")?;
+		}
+		match self.code {
+			event::KeyCode::Esc			=> write!(f, "Esc"),
+			event::KeyCode::Enter		=> write!(f, "Enter"),
+			event::KeyCode::Tab			=> write!(f, "Tab"),
+			event::KeyCode::Char(' ')	=> write!(f, "Space"),
+			event::KeyCode::Backspace	=> write!(f, "Backspace"),
+			event::KeyCode::F(n)		=> write!(f, "F{}", n),
+			event::KeyCode::Char(c)		=> write!(f, "{}", c),
+			_ => write!(f, "?"),
 		}
 	}
+}
 
-	pub fn lock(&mut self) {
-		self.time_locked = true;
-		self.end_time = Instant::now();
-	}
+#[derive(Debug, Clone, Copy)]
+pub struct KeyBindings {
+	pub quit: KeyBinding,
+	pub restart: KeyBinding,
+	pub pause: KeyBinding,
+	pub key_map: KeyBinding,
+}
 
-}
\ No newline at end of file
+impl Default for KeyBindings {
+	fn default() -> Self {
+		Self {
+			quit: KeyBinding::new(event::KeyCode::Char('q'), event::KeyModifiers::CONTROL),
+			restart: KeyBinding::new(event::KeyCode::Char('r'), event::KeyModifiers::CONTROL),
+			pause: KeyBinding::new(event::KeyCode::Char('p'), event::KeyModifiers::CONTROL),
+			key_map: KeyBinding::new(event::KeyCode::Char('k'), event::KeyModifiers::CONTROL),
+		}
+	}
+}
@@ -0,0 +1,263 @@
+use std::{io, fs, fmt::Write, collections::HashMap};
+
+/// Every initial of Mandarin, `y` and `w` included
+pub const INITIALS: [&str; 23] = [
+	"b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "j", "q", "x",
+	"zh", "ch", "sh", "r", "z", "c", "s", "y", "w",
+];
+
+/// Finals that make a syllable on their own, without initial
+pub const ZERO_INITIAL_FINALS: [&str; 12] = ["a", "o", "e", "ai", "ei", "ao", "ou", "an", "en", "ang", "eng", "er"];
+
+#[derive(Clone)]
+pub struct KeyMap {
+	leader: char,
+	name: String,
+	pub map: HashMap<String, Vec<char>>,
+	split_er: bool,
+	/// Keys of the tones 1 to 5, `tone1` to `tone5` in the file, the digits by default
+	tones: [Vec<char>;5],
+	/// Both keys of a syllable without initial, by final, `zero_<final>` in the file
+	///
+	/// Takes precedence over `leader`, `leader_er` and `split_er`.
+	zero: HashMap<String, [char;2]>,
+}
+
+impl KeyMap {
+	/// Reads a scheme file, `v` and `ve` standing for `ü` and `üe` unless those are given
+	pub fn open(filepath: &str) -> Result<KeyMap, io::Error> {
+		let file = fs::read_to_string(filepath)?;
+		let mut map = HashMap::new();
+		let mut leader = 'o';
+		let mut split_er = true;
+		let mut name:String = String::new();
+		let mut tones = ['1', '2', '3', '4', '5'].map(|digit| vec![digit]);
+		let mut zero = HashMap::new();
+		for line in file.split('\n') {
+			if line.trim().starts_with('#') || line.trim().is_empty() {
+				continue;
+			}
+			let split: Vec<&str> = line.split(&[':'][..]).collect();
+			let phoneme = split[0].trim().to_owned();
+			let key:Vec<char> = split[1].trim().chars().collect();
+
+			if phoneme == "leader" {
+				leader = key[0];
+			}
+			else if phoneme == "leader_er" {
+				map.insert("er".to_owned(), vec![key[0]]);
+			}
+			else if phoneme == "split_er" {
+				split_er = key[0] == '1';
+			}
+			else if phoneme == "name" {
+				name = split[1].trim().to_string();
+			}
+			else if let Some(vowel) = phoneme.strip_prefix("zero_") {
+				match key[..] {
+					[first, second] => zero.insert(vowel.to_string(), [first, second]),
+					_ => return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						format!("{}: `{}` takes exactly two keys", filepath, phoneme),
+					)),
+				};
+			}
+			else if let Some(tone @ 1..=5) = phoneme.strip_prefix("tone").and_then(|tone| tone.parse::<usize>().ok()) {
+				tones[tone - 1] = key;
+			}
+			else {
+				map.insert(phoneme, key);
+			}
+		}
+		// Most files spell ü as v, as it is typed, but the pinyin of the text has ü
+		for (typed, vowel) in [("v", "ü"), ("ve", "üe")] {
+			if !map.contains_key(vowel) {
+				if let Some(key) = map.remove(typed) {
+					map.insert(vowel.to_string(), key);
+				}
+			}
+		}
+		Ok(Self {
+			leader,
+			map,
+			split_er,
+			name,
+			tones,
+			zero,
+		})
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn set_name(&mut self, name: &str) {
+		self.name = name.to_string();
+	}
+
+	/// The phonemes of each key
+	pub fn reverse(&self) -> HashMap<char, Vec<String>> {
+		let mut reversed: HashMap<char, Vec<String>> = HashMap::new();
+		for (phoneme, keys) in self.map.iter() {
+			for key in keys {
+				reversed.entry(*key).or_default().push(phoneme.to_string());
+			}
+		}
+		reversed
+	}
+
+	/// The scheme in the format read by `open`
+	pub fn to_file(&self) -> String {
+		let keys = |keys: &[char]| keys.iter().collect::<String>();
+		let mut file = String::new();
+		writeln!(file, "name\t:\t{}", self.name).unwrap();
+		writeln!(file, "# 声母").unwrap();
+		for initial in INITIALS.iter().filter(|initial| self.map.contains_key(**initial)) {
+			writeln!(file, "{}\t:\t{}", initial, keys(&self.map[*initial])).unwrap();
+		}
+		writeln!(file, "# 韵母").unwrap();
+		let mut finals: Vec<_> = self.map.iter().filter(|(phoneme, _)| !INITIALS.contains(&phoneme.as_str()) && *phoneme != "er").collect();
+		finals.sort_unstable();
+		for (vowel, key) in finals {
+			writeln!(file, "{}\t:\t{}", vowel, keys(key)).unwrap();
+		}
+		writeln!(file, "# 零声母").unwrap();
+		writeln!(file, "leader\t:\t{}", self.leader).unwrap();
+		if let Some(key) = self.map.get("er") {
+			writeln!(file, "leader_er\t:\t{}", keys(key)).unwrap();
+		}
+		writeln!(file, "split_er\t:\t{}", self.split_er as u8).unwrap();
+		let mut zero: Vec<_> = self.zero.iter().collect();
+		zero.sort_unstable();
+		for (vowel, key) in zero {
+			writeln!(file, "zero_{}\t:\t{}", vowel, keys(key)).unwrap();
+		}
+		for (tone, key) in self.tones.iter().enumerate() {
+			if *key != [char::from(b'1' + tone as u8)] {
+				writeln!(file, "tone{}\t:\t{}", tone + 1, keys(key)).unwrap();
+			}
+		}
+		file
+	}
+
+	/// The first key of a syllable without initial, `*` for the key of the first letter of the final
+	pub fn leader(&self) -> char {
+		self.leader
+	}
+
+	/// Whether [e] + [r] types `er`
+	pub fn split_er(&self) -> bool {
+		self.split_er
+	}
+
+	/// The explicit keys of the syllables without initial, by final
+	pub fn zero_rules(&self) -> &HashMap<String, [char;2]> {
+		&self.zero
+	}
+
+	/// Keys that type the initial (`first`) or the final of `pinyin`, which is split
+	/// into `[initial, final]` as `Pin::pinyin_splitted`
+	///
+	/// Empty if the scheme has no way to type it.
+	pub fn keys(&self, pinyin: &[String;2], first: bool) -> Vec<char> {
+		let map = &self.map;
+		let get = |phoneme: &str| map.get(phoneme).cloned().unwrap_or_default();
+		let zero_initial = pinyin[0].is_empty();
+		if let Some(keys) = self.zero.get(&pinyin[1]).filter(|_| zero_initial) {
+			vec![keys[if first { 0 } else { 1 }]]
+		}
+		else if zero_initial && pinyin[1] == "er" && self.split_er {
+			// 处理特殊的"er"
+			// [e] + [r] => er, while [leader] + [leader_er] still works
+			let mut keys = get(if first { "e" } else { "r" });
+			let leader_keys = if first { self.leader_keys(&pinyin[1]) } else { get("er") };
+			keys.extend(leader_keys.into_iter().filter(|key| !keys.contains(key)).collect::<Vec<_>>());
+			keys
+		}
+		else if first {
+			if zero_initial {
+				// 零声母
+				self.leader_keys(&pinyin[1])
+			}
+			else {
+				get(&pinyin[0])
+			}
+		}
+		else {
+			get(&pinyin[1])
+		}
+	}
+
+	/// The two keys of a syllable, split as `Pin::pinyin_splitted`, taking the first of
+	/// each alternative. `None` if the scheme has no way to type it.
+	pub fn encode(&self, pinyin: &[String;2]) -> Option<[char;2]> {
+		Some([*self.keys(pinyin, true).first()?, *self.keys(pinyin, false).first()?])
+	}
+
+	/// Keys that type `tone`, 1 to 5
+	pub fn tone_keys(&self, tone: u8) -> Vec<char> {
+		self.tones.get(tone as usize - 1).cloned().unwrap_or_default()
+	}
+
+	/// The first key of a syllable without initial
+	fn leader_keys(&self, vowel: &str) -> Vec<char> {
+		match self.leader {
+			'*' => vowel.get(0..1).and_then(|phoneme| self.map.get(phoneme)).cloned().unwrap_or_default(),
+			c => vec![c],
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs};
+
+	use super::*;
+
+	fn syllable(initial: &str, vowel: &str) -> [String;2] {
+		[initial.to_string(), vowel.to_string()]
+	}
+
+	#[test]
+	fn keys_follow_the_rules_for_syllables_without_initial() {
+		let ms = KeyMap::open("./keymap/ms").unwrap();
+		assert_eq!(ms.encode(&syllable("zh", "uang")), Some(['v', 'd']));
+		assert_eq!(ms.encode(&syllable("", "ang")), Some(['o', 'h']));
+		assert_eq!(ms.keys(&syllable("", "er"), true), ['e', 'o']);
+		assert_eq!(ms.keys(&syllable("l", "ue"), false), ['t', 'v']);
+		let plus = KeyMap::open("./keymap/++").unwrap();
+		assert_eq!(plus.encode(&syllable("", "ang")), Some(['a', 'g']));
+		assert_eq!(plus.encode(&syllable("", "er")), Some(['e', 'q']));
+		let xh = KeyMap::open("./keymap/xh").unwrap();
+		assert_eq!(xh.encode(&syllable("", "ang")), Some(['a', 'h']));
+		assert_eq!(xh.encode(&syllable("x", "iong")), Some(['x', 's']));
+		// `v` and `ve` in the file type ü and üe
+		assert_eq!(xh.encode(&syllable("n", "ü")), Some(['n', 'v']));
+		assert_eq!(xh.encode(&syllable("l", "üe")), Some(['l', 't']));
+		assert_eq!(ms.encode(&syllable("n", "ü")), Some(['n', 'y']));
+	}
+
+	#[test]
+	fn to_file_reads_back_the_same_scheme() {
+		let path = env::temp_dir().join(format!("spcli-keymap-{}", std::process::id()));
+		for name in ["ms", "xh", "gb"] {
+			let key_map = KeyMap::open(&format!("./keymap/{}", name)).unwrap();
+			fs::write(&path, key_map.to_file()).unwrap();
+			let read = KeyMap::open(path.to_str().unwrap()).unwrap();
+			assert_eq!(read.name(), key_map.name());
+			assert_eq!(read.map, key_map.map);
+			assert_eq!(read.zero_rules(), key_map.zero_rules());
+			assert_eq!((read.leader(), read.split_er()), (key_map.leader(), key_map.split_er()));
+		}
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn zero_rules_take_two_keys() {
+		let path = env::temp_dir().join(format!("spcli-zero-{}", std::process::id()));
+		fs::write(&path, "name : x\nzero_ang : a\n").unwrap();
+		let err = KeyMap::open(path.to_str().unwrap()).err().unwrap();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+		fs::remove_file(&path).unwrap();
+	}
+}
@@ -0,0 +1,31 @@
+//! Shuangpin practice
+//!
+//! `Session` is the typing engine: feed it keys, it checks them against a `KeyMap` and
+//! keeps the `Passage` and the `Counter` up to date, without touching the terminal.
+//! `Input` runs a `Session` in the terminal, drawing through a `Backend`.
+
+pub mod pin;
+pub mod keymap;
+pub mod session;
+pub mod input;
+pub mod render;
+pub mod backend;
+pub mod theme;
+pub mod i18n;
+pub mod cli;
+pub mod config;
+pub mod script;
+pub mod hanzi;
+pub mod punctuation;
+pub mod store;
+pub mod history;
+pub mod mastery;
+pub mod review;
+pub mod analyze;
+pub mod optimize;
+pub mod codec;
+pub mod words;
+
+pub use keymap::KeyMap;
+pub use pin::{Document, Passage};
+pub use session::{Session, Outcome, Counter};
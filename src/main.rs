@@ -1,31 +1,189 @@
-use std::process::exit;
-
-mod input;
-mod pin;
-mod render;
-mod cli;
+use std::{io::{self, Read}, fs, process::exit};
 
 use clap::Parser;
-use input::Input;
-use crossterm::terminal;
-use pin::Document;
-use input::KeyMap;
-use cli::Args;
+use spcli::{
+    input::{self, Input, Options},
+    render::Keyboard,
+    backend::Terminal,
+    pin::Document,
+    keymap::KeyMap,
+    punctuation::PunctuationMap,
+    cli::{Args, Command, ConfigAction},
+    config::{Config, Settings},
+    script,
+    history,
+    review::{self, Schedule},
+    mastery::Mastery,
+    analyze::{self, Analysis},
+    optimize::{self, Corpus},
+    codec,
+};
 fn main() {
+    // Before the config, so that `--help` works whatever the file holds
     let args = Args::parse();
-    let mut doc = Document::open(&args.text).unwrap();
-    let key_map = KeyMap::open(&args.keymap).unwrap();
-    loop {
-        let restart = Input::new(&mut doc, &key_map, args.pinyin).run();
-        if !restart {
-            break;
-        }
+    let config = Config::load().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+    let settings = Settings::resolve(config, &args);
+    match &args.command {
+        Some(Command::Config { action: ConfigAction::Show }) => print!("{}", settings.show()),
+        Some(Command::History) => match history::load() {
+            Ok(records) => print!("{}", history::summary(&records)),
+            Err(err) => {
+                eprintln!("{}", err);
+                exit(1);
+            },
+        },
+        Some(Command::Analyze { text, keymap }) => {
+            let text = if text.is_empty() { &settings.text.value } else { text };
+            let keymap = if keymap.is_empty() { vec![settings.keymap.value.clone()] } else { keymap.clone() };
+            let doc = open_document(text, &settings);
+            let layout = settings.layout.value.rows();
+            let mut analyses = Vec::new();
+            for path in keymap.iter() {
+                let key_map = KeyMap::open(path).unwrap_or_else(|err| {
+                    eprintln!("{}: {}", path, err);
+                    exit(1);
+                });
+                analyses.push(Analysis::new(&doc, &key_map, &Keyboard::new(&key_map, layout.clone())));
+            }
+            print!("{}", analyze::report(&analyses, &layout));
+        },
+        Some(Command::Optimize { text, keymap, output, name, iterations }) => {
+            let text = if text.is_empty() { &settings.text.value } else { text };
+            let doc = open_document(text, &settings);
+            let base = KeyMap::open(keymap.as_ref().unwrap_or(&settings.keymap.value)).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            });
+            let corpus = Corpus::new(&doc, &Keyboard::new(&base, settings.layout.value.rows()));
+            let mut optimized = optimize::optimize(&corpus, &base, *iterations).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            });
+            optimized.key_map.set_name(&name.clone().unwrap_or_else(|| format!("{}优化", base.name())));
+            if let Err(err) = fs::write(output, optimized.key_map.to_file()) {
+                eprintln!("{}: {}", output, err);
+                exit(1);
+            }
+            println!("{}: {:.3} -> {:.3}", output, optimized.base_cost, optimized.cost);
+        },
+        Some(Command::Encode { file, keymap, interleave }) => {
+            let key_map = KeyMap::open(keymap.as_ref().unwrap_or(&settings.keymap.value)).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            });
+            print!("{}", codec::encode(&read_input(file.as_deref()), &key_map, *interleave));
+        },
+        Some(Command::Decode { keys, keymap }) => {
+            let key_map = KeyMap::open(keymap.as_ref().unwrap_or(&settings.keymap.value)).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            });
+            let keys = keys.clone().unwrap_or_else(|| read_input(None));
+            for line in keys.lines() {
+                println!("{}", codec::decode(line, &key_map));
+            }
+        },
+        Some(Command::Script { file, passage, screen }) => {
+            let (doc, key_map, options) = open_practice(&settings);
+            let script = read_input(file.as_deref());
+            let events = script::parse(&script).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            });
+            match script::run(&doc, *passage, &key_map, &options, &events, *screen) {
+                Ok(report) => print!("{}", report),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                },
+            }
+        },
+        Some(Command::Review) => {
+            let (doc, key_map, options) = open_practice(&settings);
+            let mut restarted = false;
+            loop {
+                let schedule = Schedule::load(&options.rules.scheme(&key_map)).unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    exit(1);
+                });
+                let passage = match review::passage(&doc, &schedule, review::today()) {
+                    Some(passage) => passage,
+                    None => {
+                        if restarted {
+                            // Everything has been reviewed since the last launch
+                            let _ = input::leave();
+                        }
+                        println!("{}", settings.lang.value.messages().nothing_due);
+                        return;
+                    },
+                };
+                let input = Input::with_passage(passage, &key_map, &options, Terminal::new()).with_schedule(schedule);
+                restarted = with_progress(input, &options, &key_map).run();
+                if !restarted {
+                    break;
+                }
+            }
+        },
+        None => {
+            let (doc, key_map, options) = open_practice(&settings);
+            loop {
+                let restart = with_progress(Input::new(&doc, &key_map, &options), &options, &key_map).run();
+                if !restart {
+                    break;
+                }
+            }
+        },
+    }
+}
+
+/// The text, the keymap and the options to practice with
+fn open_practice(settings: &Settings) -> (Document, KeyMap, Options) {
+    let doc = open_document(&settings.text.value, settings);
+    let key_map = KeyMap::open(&settings.keymap.value).unwrap_or_else(|err| {
+        eprintln!("{}: {}", settings.keymap.value, err);
+        exit(1);
+    });
+    let mut options = settings.options();
+    if settings.punctuation.value {
+        let path = &settings.punctuation_map.value;
+        options.rules.punctuation = Some(PunctuationMap::open(path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path, err);
+            exit(1);
+        }));
     }
+    (doc, key_map, options)
 }
 
-fn die() {
-    if terminal::is_raw_mode_enabled().expect("Can not read if raw mode is enabled") {
-        terminal::disable_raw_mode().expect("Failed to disable raw mode");
+/// The passages of `paths`, converted as the settings say
+fn open_document(paths: &[String], settings: &Settings) -> Document {
+    Document::open_all(paths, settings.convert.value).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    })
+}
+
+/// Gives `input` the mastery kept in the data directory, with `--fade`.
+/// A file that can not be read is left out, it never stops the practice.
+fn with_progress<'b>(input: Input<'b>, options: &Options, key_map: &KeyMap) -> Input<'b> {
+    match options.fade.and_then(|_| Mastery::load(&options.rules.scheme(key_map)).ok()) {
+        Some(mastery) => input.with_mastery(mastery),
+        None => input,
     }
-    exit(0);
+}
+
+/// The content of `file`, or of the standard input if it is `None` or `-`
+fn read_input(file: Option<&str>) -> String {
+    match file {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
+        },
+        Some(file) => fs::read_to_string(file),
+    }.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    })
 }
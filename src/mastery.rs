@@ -0,0 +1,34 @@
+use std::io;
+
+use crate::store::{Store, Table};
+
+const FILE: &str = "mastery.tsv";
+
+/// How many times in a row each syllable was typed right, as kept in `mastery.tsv`
+pub struct Mastery {
+	streaks: Table<u32>,
+}
+
+impl Mastery {
+	pub fn load(scheme: &str) -> Result<Self, io::Error> {
+		Ok(Self { streaks: Table::load(Store::data(), FILE, scheme)? })
+	}
+
+	/// Writes back every scheme, `scheme syllable streak` separated by tabs
+	pub fn save(&self) -> Result<(), io::Error> {
+		self.streaks.save()
+	}
+
+	/// Right answers in a row for `syllable`, pinyin without tone
+	pub fn streak(&self, syllable: &str) -> u32 {
+		self.streaks.get(syllable).copied().unwrap_or(0)
+	}
+
+	pub fn pass(&mut self, syllable: &str) {
+		*self.streaks.get_or_insert_with(syllable, || 0) += 1;
+	}
+
+	pub fn fail(&mut self, syllable: &str) {
+		self.streaks.insert(syllable, 0);
+	}
+}
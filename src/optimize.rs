@@ -0,0 +1,231 @@
+use std::{io, collections::{HashMap, HashSet}};
+
+use rand::{Rng, seq::SliceRandom};
+
+use crate::{pin::{self, Document}, keymap::{KeyMap, INITIALS}, render::Keyboard, analyze::{key_cost, bigram_cost}};
+
+/// Finals that keep their key, as in most schemes: the single vowels, and `er` with its own rules
+const FIXED_FINALS: [&str; 6] = ["a", "o", "e", "i", "u", "er"];
+
+/// Keys the finals are given, the letters and `;` as some schemes use it
+const KEYS: [char; 27] = [
+	'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm',
+	'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', ';',
+];
+
+/// Temperature of the annealing at the first step, in effort per key press
+const START_TEMPERATURE: f64 = 0.02;
+
+/// The syllables of a corpus and how they follow each other, to weigh schemes by
+pub struct Corpus {
+	syllables: Vec<[String;2]>,
+	/// Occurrences of each syllable
+	counts: Vec<u32>,
+	/// Occurrences of each syllable right after another, by index in `syllables`
+	pairs: Vec<(usize, usize, u32)>,
+	positions: HashMap<char, (u16, u16)>,
+}
+
+impl Corpus {
+	/// Reads the syllables of every passage of `document`, the keys being placed as `keyboard` shows them
+	pub fn new(document: &Document, keyboard: &Keyboard) -> Self {
+		let mut indices: HashMap<[String;2], usize> = HashMap::new();
+		let mut syllables = Vec::new();
+		let mut counts = Vec::new();
+		let mut pairs: HashMap<(usize, usize), u32> = HashMap::new();
+		for passage in document.passages.iter() {
+			let mut last = None;
+			for pinyin in passage.chars.iter().filter_map(|c| c.pinyin.as_ref()) {
+				let index = *indices.entry(pinyin.pinyin_splitted.clone()).or_insert_with(|| {
+					syllables.push(pinyin.pinyin_splitted.clone());
+					counts.push(0);
+					syllables.len() - 1
+				});
+				counts[index] += 1;
+				if let Some(last) = last {
+					*pairs.entry((last, index)).or_insert(0) += 1;
+				}
+				last = Some(index);
+			}
+		}
+		let positions = KEYS.iter().filter_map(|&key| keyboard.position(key).map(|position| (key, position))).collect();
+		Self {
+			syllables,
+			counts,
+			pairs: pairs.into_iter().map(|((last, next), count)| (last, next, count)).collect(),
+			positions,
+		}
+	}
+
+	/// Effort per key press of typing the corpus with `key_map`, as `Analysis` counts it.
+	/// Syllables the scheme can not type are left out.
+	pub fn cost(&self, key_map: &KeyMap) -> f64 {
+		let encoded: Vec<Option<[(u16, u16);2]>> = self.syllables.iter().map(|syllable| {
+			let [first, second] = key_map.encode(syllable)?;
+			Some([*self.positions.get(&first)?, *self.positions.get(&second)?])
+		}).collect();
+		let mut cost = 0f64;
+		let mut keystrokes = 0u32;
+		for (positions, count) in encoded.iter().zip(&self.counts) {
+			if let Some([first, second]) = positions {
+				cost += *count as f64 * (key_cost(*first) + key_cost(*second) + bigram_cost(*first, *second));
+				keystrokes += count * 2;
+			}
+		}
+		for (last, next, count) in self.pairs.iter() {
+			if let (Some(last), Some(next)) = (encoded[*last], encoded[*next]) {
+				cost += *count as f64 * bigram_cost(last[1], next[0]);
+			}
+		}
+		if keystrokes == 0 { 0f64 } else { cost / keystrokes as f64 }
+	}
+}
+
+/// A scheme found by `optimize`, with the effort of its base and its own
+pub struct Optimized {
+	pub key_map: KeyMap,
+	pub base_cost: f64,
+	pub cost: f64,
+}
+
+/// Searches by simulated annealing for the keys of the finals that type `corpus` with the
+/// least effort, starting from `base` and keeping its initials, single vowels and rules for
+/// syllables without initial. Every syllable stays typeable by a pair of keys of its own,
+/// but for those `base` already types alike, such as `lo` and `luo`.
+pub fn optimize(corpus: &Corpus, base: &KeyMap, iterations: u32) -> Result<Optimized, io::Error> {
+	let mut finals: Vec<String> = pin::syllables().iter()
+		.map(|syllable| syllable[1].clone())
+		.filter(|vowel| !FIXED_FINALS.contains(&vowel.as_str()))
+		.collect::<HashSet<_>>()
+		.into_iter()
+		.collect();
+	finals.sort_unstable();
+	let shared = conflicts(base, &HashSet::new());
+	let has_conflicts = |key_map: &KeyMap| !conflicts(key_map, &shared).is_empty();
+	let mut key_map = base.clone();
+	// What the syllables do not use, such as `v` for `ü`, goes away
+	key_map.map.retain(|phoneme, _| {
+		INITIALS.contains(&phoneme.as_str()) || FIXED_FINALS.contains(&phoneme.as_str()) || finals.contains(phoneme)
+	});
+	for vowel in finals.iter() {
+		let key = key_map.map.get(vowel).and_then(|keys| keys.first()).copied().filter(|key| KEYS.contains(key));
+		match key {
+			Some(key) => key_map.map.insert(vowel.clone(), vec![key]),
+			None => key_map.map.remove(vowel),
+		};
+	}
+	if has_conflicts(&key_map) {
+		for vowel in finals.iter() {
+			key_map.map.remove(vowel);
+		}
+	}
+	for vowel in finals.iter() {
+		if key_map.map.contains_key(vowel) {
+			continue;
+		}
+		let key = KEYS.iter().find(|key| {
+			key_map.map.insert(vowel.clone(), vec![**key]);
+			!has_conflicts(&key_map)
+		});
+		if key.is_none() {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, format!("No key is left for `{}`", vowel)));
+		}
+	}
+
+	let mut rng = rand::thread_rng();
+	let mut cost = corpus.cost(&key_map);
+	let mut best = (cost, key_map.clone());
+	for step in 0..iterations {
+		let temperature = START_TEMPERATURE * (1f64 - step as f64 / iterations as f64);
+		let vowel = finals.choose(&mut rng).unwrap();
+		let old = key_map.map[vowel].clone();
+		let other = if rng.gen_bool(0.5) {
+			// Moves the final to another key
+			key_map.map.insert(vowel.clone(), vec![*KEYS.choose(&mut rng).unwrap()]);
+			None
+		}
+		else {
+			// Swaps the keys of two finals
+			let other = finals.choose(&mut rng).unwrap();
+			let other_keys = key_map.map.insert(other.clone(), old.clone()).unwrap();
+			key_map.map.insert(vowel.clone(), other_keys.clone());
+			Some((other, other_keys))
+		};
+		let revert = |key_map: &mut KeyMap| {
+			key_map.map.insert(vowel.clone(), old.clone());
+			if let Some((other, other_keys)) = other.as_ref() {
+				key_map.map.insert(other.to_string(), other_keys.clone());
+			}
+		};
+		if has_conflicts(&key_map) {
+			revert(&mut key_map);
+			continue;
+		}
+		let new_cost = corpus.cost(&key_map);
+		if new_cost <= cost || rng.gen::<f64>() < ((cost - new_cost) / temperature).exp() {
+			cost = new_cost;
+			if cost < best.0 {
+				best = (cost, key_map.clone());
+			}
+		}
+		else {
+			revert(&mut key_map);
+		}
+	}
+	Ok(Optimized {
+		key_map: best.1,
+		base_cost: corpus.cost(base),
+		cost: best.0,
+	})
+}
+
+/// Pairs of syllables that `key_map` types with the same keys, but for those in `allowed`
+fn conflicts<'a>(key_map: &KeyMap, allowed: &HashSet<(&'a [String;2], &'a [String;2])>) -> HashSet<(&'a [String;2], &'a [String;2])> {
+	let mut seen: HashMap<[char;2], &[String;2]> = HashMap::new();
+	let mut conflicts = HashSet::new();
+	for syllable in pin::syllables() {
+		if let Some(keys) = key_map.encode(syllable) {
+			if let Some(other) = seen.insert(keys, syllable) {
+				if !allowed.contains(&(other, syllable)) {
+					conflicts.insert((other, syllable));
+				}
+			}
+		}
+	}
+	conflicts
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs};
+
+	use super::*;
+	use crate::{pin::Passage, render::LayoutType};
+
+	#[test]
+	fn optimized_schemes_keep_what_is_fixed_and_read_back() {
+		let base = KeyMap::open("./keymap/xh").unwrap();
+		let keyboard = Keyboard::new(&base, LayoutType::Qwerty.rows());
+		let document = Document { passages: vec![
+			Passage::new(String::from("我们学习汉语，写了很多字。")),
+			Passage::new(String::from("女儿略懂英文，爱看窗外的雪。")),
+		] };
+		let corpus = Corpus::new(&document, &keyboard);
+		let result = optimize(&corpus, &base, 300).unwrap();
+
+		let allowed = conflicts(&base, &HashSet::new());
+		assert!(conflicts(&result.key_map, &allowed).is_empty());
+		for phoneme in INITIALS.iter().chain(FIXED_FINALS.iter()) {
+			assert_eq!(result.key_map.map.get(*phoneme), base.map.get(*phoneme), "{}", phoneme);
+		}
+		assert_eq!(result.key_map.zero_rules(), base.zero_rules());
+		assert_eq!(result.cost, corpus.cost(&result.key_map));
+
+		let path = env::temp_dir().join(format!("spcli-optimize-{}", std::process::id()));
+		fs::write(&path, result.key_map.to_file()).unwrap();
+		let read = KeyMap::open(path.to_str().unwrap()).unwrap();
+		assert_eq!(read.map, result.key_map.map);
+		assert_eq!(read.zero_rules(), result.key_map.zero_rules());
+		fs::remove_file(&path).unwrap();
+	}
+}
@@ -1,10 +1,25 @@
-use std::{fs, io, cmp, fmt::Write};
-use crossterm::{Command, style::{PrintStyledContent, Stylize, Color}};
+use std::{fs, io, cmp, fmt::Write, collections::BTreeSet, sync::OnceLock};
 use rand::{self, Rng};
 use pinyin::{ToPinyin, Pinyin};
 use unicode_width::UnicodeWidthChar;
 use core::fmt::Debug;
 
+use crate::{hanzi::{self, ConvertType}, words};
+
+/// Every syllable read by a CJK Unified Ideograph, split as `Pin::pinyin_splitted`, without
+/// those made of an initial alone such as `n` and `hm`
+pub fn syllables() -> &'static [[String;2]] {
+	static SYLLABLES: OnceLock<Vec<[String;2]>> = OnceLock::new();
+	SYLLABLES.get_or_init(|| {
+		let syllables: BTreeSet<[String;2]> = ('\u{4e00}'..='\u{9fa5}')
+			.filter_map(|c| c.to_pinyin())
+			.map(|pinyin| Pin::new(pinyin).pinyin_splitted)
+			.filter(|pinyin| !pinyin[1].is_empty())
+			.collect();
+		syllables.into_iter().collect()
+	})
+}
+
 #[derive(Debug)]
 pub struct Document {
 	pub passages: Vec<Passage>,
@@ -12,23 +27,36 @@ pub struct Document {
 
 
 impl Document {
-	pub fn open(filepath: &str) -> Result<Self, io::Error> {
-		let file = fs::read_to_string(filepath).unwrap();
+	/// Reads the passages of a file, converting them to `convert` on the way
+	pub fn open(filepath: &str, convert: ConvertType) -> Result<Self, io::Error> {
+		let file = fs::read_to_string(filepath).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", filepath, err)))?;
 		let passages: Vec<Passage> 
 		= file.split("\n\n").map(|passage| {
-			Passage::new(passage.to_string())
+			Passage::new(convert.convert(passage))
 		}).collect();
 		Ok(Self {
 			passages,
 		})
 	}
 
+	/// Opens every file and puts their passages together
+	pub fn open_all(filepaths: &[String], convert: ConvertType) -> Result<Self, io::Error> {
+		let mut passages = Vec::new();
+		for filepath in filepaths {
+			passages.append(&mut Self::open(filepath, convert)?.passages);
+		}
+		Ok(Self {
+			passages,
+		})
+	}
+
 	pub fn get_random(&self) -> usize {
 		rand::thread_rng().gen_range(0..self.passages.len())
 	}
 }
 
 
+#[derive(Clone)]
 pub struct Passage {
 	pub chars: Vec<Character>,
 }
@@ -46,12 +74,29 @@ impl Passage {
 				);
 			}
 		});
+		let letters: Vec<char> = chars.iter().map(|c| c.char).collect();
+		let mut start = 0;
+		for len in words::segment(&letters) {
+			for c in &mut chars[start..start + len - 1] {
+				c.word_end = false;
+			}
+			start += len;
+		}
 		Self {
 			chars,
 		}
 	}
+
+	/// Whether a space goes after `chars[index]` when words are spaced: it ends a word,
+	/// and neither it nor the next character is a mark
+	pub fn word_gap(&self, index: usize) -> bool {
+		let character = &self.chars[index];
+		character.word_end && !character.is_mark && self.chars.get(index + 1).is_some_and(|next| !next.is_mark)
+	}
+	/// A character reads as itself, Traditional ones too (`乾` as `qian`, `髮` as `fa`), and
+	/// as its Simplified form only when it has no reading of its own
 	pub fn get_pinyin(string: String) -> Vec<Option<Pin>> {
-		let pinyin_raw = string.as_str().to_pinyin();
+		let pinyin_raw = string.chars().map(|c| c.to_pinyin().or_else(|| hanzi::to_simplified(c).to_pinyin()));
 		let mut result = Vec::new();
 		pinyin_raw.for_each(|pinyin| {
 			if let Some(pinyin) = pinyin {
@@ -65,12 +110,13 @@ impl Passage {
 		});
 		result
 	}
-	pub fn wrap(&self, max_width: u16, consider_pinyin: bool) -> Vec<&[Character]> {
+	/// With `word_spacing`, every `word_gap` takes one more column
+	pub fn wrap(&self, max_width: u16, consider_pinyin: bool, word_spacing: bool) -> Vec<&[Character]> {
 		let mut result = Vec::new();
 		let mut width = 0;
 		let mut slow = 0;
 		let mut fast = 0;
-		for c in &self.chars {
+		for (index, c) in self.chars.iter().enumerate() {
 			// break line when the line width exceeds the max_width
 			// if `consider_pinyin`: A character's width will be max(char width, pinyin width + 1);
 			// 'shang '
@@ -81,6 +127,9 @@ impl Passage {
 					char_width = cmp::max(char_width, pinyin.pinyin.len() + 1);
 				}
 			}
+			if word_spacing && self.word_gap(index) {
+				char_width += 1;
+			}
 			if width + char_width > max_width.into() {
 				result.push(&self.chars[slow..=fast]);
 				width = 0;
@@ -113,19 +162,46 @@ impl Debug for Passage {
     }
 }
 
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum CharStatus {
 	Passed,
 	Typing,
 	TypingHalf,
 	ErrorConsonant,
 	ErrorVowel,
+	/// Wrong key for a character typed with one key, eg. a letter or a punctuation mark,
+	/// or for the space committing a word
+	ErrorMark,
+	/// Wrong key for the tone
+	ErrorTone,
+	/// The last character of a word is typed, and waits for the space committing the word
+	Uncommitted,
 	Future,
 }
 
+impl CharStatus {
+	pub fn name(&self) -> &'static str {
+		match self {
+			CharStatus::Passed			=> "passed",
+			CharStatus::Typing			=> "typing",
+			CharStatus::TypingHalf		=> "typing-half",
+			CharStatus::ErrorConsonant	=> "error-consonant",
+			CharStatus::ErrorVowel		=> "error-vowel",
+			CharStatus::ErrorMark		=> "error-mark",
+			CharStatus::ErrorTone		=> "error-tone",
+			CharStatus::Uncommitted		=> "uncommitted",
+			CharStatus::Future			=> "future",
+		}
+	}
+}
+
+#[derive(Clone)]
 pub struct Character {
 	pub char: char,
 	pub pinyin: Option<Pin>,
 	pub is_mark: bool,
+	/// The last character of its word, as `words::segment` cuts the passage
+	pub word_end: bool,
 	pub status: CharStatus,
 }
 
@@ -135,6 +211,7 @@ impl Character {
 			is_mark: pinyin.is_none(),
 			char,
 			pinyin,
+			word_end: true,
 			status: CharStatus::Future,
 		}
 	}
@@ -142,68 +219,28 @@ impl Character {
 	pub fn set_status(&mut self, status: CharStatus) {
 		self.status = status;
 	}
-
-	pub fn char_style(&self, is_live: bool) -> impl Command {
-		if is_live {
-			match self.status {
-				CharStatus::Passed			=> PrintStyledContent(self.char.with(Color::Green)),
-				CharStatus::Typing			=> PrintStyledContent(self.char.with(Color::Blue)),
-				CharStatus::TypingHalf		=> PrintStyledContent(self.char.with(Color::Cyan)),
-				CharStatus::ErrorConsonant	|
-				CharStatus::ErrorVowel		=> PrintStyledContent(self.char.with(Color::Red)),
-				CharStatus::Future			=> PrintStyledContent(self.char.with(Color::White)),
-			}
-		}
-		else {
-			match self.status {
-				CharStatus::Passed => PrintStyledContent(self.char.with(Color::DarkGreen)),
-				CharStatus::Future => PrintStyledContent(self.char.with(Color::Grey)),
-				/*Invalid*/ _ => PrintStyledContent(self.char.with(Color::Grey)),
-			}
-		}
-	}
-
-	pub fn pinyin_style(&self, is_live: bool) -> [impl Command;2] {
-		let pinyin = if self.is_mark { String::new() } else { self.pinyin.as_ref().unwrap().pinyin_with_tone.clone() };
-		let consonant = if self.is_mark { String::new() } else { self.pinyin.as_ref().unwrap().pinyin_splitted[0].clone() };
-		let vowel = pinyin[consonant.len()..pinyin.len()].to_string();
-		if is_live {
-			match self.status {
-				/*🟩🟩*/ CharStatus::Passed			 => [PrintStyledContent(consonant.with(Color::Green)),	PrintStyledContent(vowel.with(Color::Green).bold())],
-				/*🟦🟦*/ CharStatus::Typing			 => [PrintStyledContent(consonant.with(Color::Blue )),	PrintStyledContent(vowel.with(Color::Blue ).bold())],
-				/*🟩🟦*/ CharStatus::TypingHalf		 => [PrintStyledContent(consonant.with(Color::Green)),	PrintStyledContent(vowel.with(Color::Blue ).bold())],
-				/*🟥🟦*/ CharStatus::ErrorConsonant	 => [PrintStyledContent(consonant.with(Color::Red  )),	PrintStyledContent(vowel.with(Color::Green).bold())],
-				/*🟩🟥*/ CharStatus::ErrorVowel		 => [PrintStyledContent(consonant.with(Color::Green)),	PrintStyledContent(vowel.with(Color::Red  ).bold())],
-				/*⬜⬜*/ CharStatus::Future		   => [PrintStyledContent(consonant.with(Color::White)),  PrintStyledContent(vowel.with(Color::White).bold())],
-			}
-		}
-		else {
-			match self.status {
-				/*🟩🟩*/ CharStatus::Passed	=> [PrintStyledContent(consonant.with(Color::DarkGreen)),	PrintStyledContent(vowel.with(Color::DarkGreen).bold())],
-				/*⬜⬜*/ CharStatus::Future => [PrintStyledContent(consonant.with(Color::Grey )),  PrintStyledContent(vowel.with(Color::Grey ).bold())],
-				/*Invalid*/ _ => [PrintStyledContent(consonant.with(Color::Grey)),  PrintStyledContent(vowel.with(Color::Grey))],
-			}
-		}
-	}
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pin {
 	pub pinyin_with_tone: String,
 	pub pinyin: String,
-	pub pinyin_splitted: [String;2]
+	pub pinyin_splitted: [String;2],
+	/// 1 to 4, 5 for the neutral tone
+	pub tone: u8,
 }
 
 impl Pin {
 	pub fn new(pinyin: Pinyin) -> Self{
 		Self {
 			pinyin_with_tone: pinyin.with_tone().to_string(),
-			pinyin_splitted: Self::split(pinyin.plain().to_string()),
+			pinyin_splitted: Self::split(pinyin.plain()),
 			pinyin: pinyin.plain().to_string(),
+			tone: pinyin.with_tone_num_end().chars().last().and_then(|tone| tone.to_digit(10)).unwrap_or(5) as u8,
 		}
 	}
-	fn split(pinyin: String) -> [String;2] {
+	fn split(pinyin: &str) -> [String;2] {
 		let mut result: [String;2] = [String::new(), String::new()];
 		let mut vowel_start = false;
 		for phoneme in pinyin.chars() {
@@ -224,4 +261,32 @@ impl Pin {
 		}
 		result
 	}
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn readings(text: &str) -> Vec<String> {
+		Passage::get_pinyin(text.to_string()).into_iter()
+			.map(|pin| pin.map_or_else(String::new, |pin| pin.pinyin_with_tone))
+			.collect()
+	}
+
+	#[test]
+	fn traditional_characters_keep_their_own_reading() {
+		assert_eq!(readings("乾隻髮"), ["qián", "zhī", "fà"]);
+		assert_eq!(readings("干只发"), ["gàn", "zhǐ", "fā"]);
+	}
+
+	#[test]
+	fn missing_texts_are_errors_naming_the_file() {
+		let err = Document::open_all(&[String::from("./text/text.txt"), String::from("./text/missing.txt")], ConvertType::Off).err().unwrap();
+		assert_eq!(err.kind(), io::ErrorKind::NotFound);
+		assert!(err.to_string().starts_with("./text/missing.txt: "));
+	}
+
+	#[test]
+	fn characters_without_reading_are_none() {
+		assert_eq!(readings("a，"), ["", ""]);
+	}
+}
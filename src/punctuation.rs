@@ -0,0 +1,88 @@
+use std::{io, fs, collections::HashMap};
+
+/// Keys that type each Chinese punctuation mark, as a Chinese IME does
+#[derive(Debug, Clone)]
+pub struct PunctuationMap {
+	name: String,
+	map: HashMap<char, Vec<char>>,
+}
+
+impl PunctuationMap {
+	/// Reads a file in the format of the `KeyMap`, one `mark: keys` per line
+	pub fn open(filepath: &str) -> Result<PunctuationMap, io::Error> {
+		let file = fs::read_to_string(filepath)?;
+		let mut map = HashMap::new();
+		let mut name = String::new();
+		for (no, line) in file.lines().enumerate() {
+			if line.trim().starts_with('#') || line.trim().is_empty() {
+				continue;
+			}
+			// The mark is a full-width character, so the first ASCII `:` is always the separator
+			let (mark, keys) = line.split_once(':').ok_or_else(|| {
+				io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: Expected `mark: keys`", filepath, no + 1))
+			})?;
+			let mark = mark.trim();
+			if mark == "name" {
+				name = keys.trim().to_string();
+				continue;
+			}
+			let mut chars = mark.chars();
+			match (chars.next(), chars.next()) {
+				(Some(mark), None) => {
+					map.insert(mark, keys.trim().chars().collect());
+				},
+				_ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: `{}` is not a single mark", filepath, no + 1, mark))),
+			}
+		}
+		Ok(Self {
+			name,
+			map,
+		})
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Keys that type `mark`, `None` if it is not typed at all
+	pub fn keys(&self, mark: char) -> Option<&Vec<char>> {
+		self.map.get(&mark)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs};
+
+	use super::*;
+
+	/// Opens `file` written to a temporary path, `name` telling the tests apart
+	fn open(name: &str, file: &str) -> Result<PunctuationMap, io::Error> {
+		let path = env::temp_dir().join(format!("spcli-punctuation-{}-{}", std::process::id(), name));
+		fs::write(&path, file).unwrap();
+		let map = PunctuationMap::open(path.to_str().unwrap());
+		fs::remove_file(&path).unwrap();
+		map
+	}
+
+	#[test]
+	fn marks_take_the_keys_after_the_first_colon() {
+		let map = open("marks", "name\t:\t测试\n# 注释\n：\t:\t:\n，\t:\t,\n“\t:\t\"\n").unwrap();
+		assert_eq!(map.name(), "测试");
+		assert_eq!(map.keys('：'), Some(&vec![':']));
+		assert_eq!(map.keys('，'), Some(&vec![',']));
+		assert_eq!(map.keys('“'), Some(&vec!['"']));
+		assert_eq!(map.keys('。'), None);
+		let default = PunctuationMap::open("./punctuation/default").unwrap();
+		assert_eq!(default.keys('”'), Some(&vec!['"']));
+	}
+
+	#[test]
+	fn broken_lines_are_errors() {
+		let err = open("mark", "，\t:\t,\n。。\t:\t.\n").unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+		assert!(err.to_string().ends_with(":2: `。。` is not a single mark"));
+		let err = open("colon", "，,\n").unwrap_err();
+		assert!(err.to_string().ends_with(":1: Expected `mark: keys`"));
+	}
+}
@@ -1,17 +1,15 @@
-use std::{io::{Write, stdout, Stdout, self}, cmp, collections::HashMap};
-use crossterm::{terminal, ExecutableCommand, cursor::MoveTo, style::{Stylize, Color, PrintStyledContent, SetBackgroundColor}, QueueableCommand};
+use std::{io, cmp, collections::HashMap};
+use crossterm::style::{Stylize, Color, StyledContent};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::{pin::Passage, input::{KeyMap, Counter}};
+use crate::{pin::{Passage, Character, CharStatus}, keymap::{KeyMap, INITIALS, ZERO_INITIAL_FINALS}, session::Counter, theme::Theme, i18n::Messages, backend::Backend};
 
 const PADDING_TOP: u16 = 2;
 const PADDING_ASIDE: u16 = 10;
 const MAX_WIDTH: u16 = 120;
+/// Rows of the keyboard, for the room left below the passage on short terminals
+const KEYBOARD_ROWS: u16 = 4;
 
-const MISTAKE_LABEL: &str = "错误: ";
-const SPEED_LABEL: &str = "速度: ";
-const TIME_LABEL: &str = "耗时: ";
-const LABEL_LEN:i32 = 19;
 
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum ToneType {
@@ -25,6 +23,62 @@ pub enum ToneType {
 	Off
 }
 
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LayoutType {
+	Qwerty,
+	Dvorak,
+	Colemak,
+}
+
+impl LayoutType {
+	/// The number row, then the letter rows with the punctuation keys on their right
+	pub fn rows(&self) -> [Vec<char>;4] {
+		match self {
+			LayoutType::Qwerty => [
+				vec!['1','2','3','4','5','6','7','8','9','0','-','='],
+				 vec!['q','w','e','r','t','y','u','i','o','p','[',']','\\'],
+				  vec!['a','s','d','f','g','h','j','k','l',';','\''],
+				   vec!['z','x','c','v','b','n','m',',','.','/']
+			],
+			LayoutType::Dvorak => [
+				vec!['1','2','3','4','5','6','7','8','9','0','[',']'],
+				 vec!['\'',',','.','p','y','f','g','c','r','l','/','=','\\'],
+				  vec!['a','o','e','u','i','d','h','t','n','s','-'],
+				   vec![';','q','j','k','x','b','m','w','v','z']
+			],
+			LayoutType::Colemak => [
+				vec!['1','2','3','4','5','6','7','8','9','0','-','='],
+				 vec!['q','w','f','p','g','j','l','u','y',';','[',']','\\'],
+				  vec!['a','r','s','t','d','h','n','e','i','o','\''],
+				   vec!['z','x','c','v','b','k','m',',','.','/']
+			],
+		}
+	}
+}
+
+/// Characters typed with shift on the keys that are not letters, as on a US keyboard
+const SHIFTED: [(char, char); 21] = [
+	('`', '~'), ('1', '!'), ('2', '@'), ('3', '#'), ('4', '$'), ('5', '%'), ('6', '^'),
+	('7', '&'), ('8', '*'), ('9', '('), ('0', ')'), ('-', '_'), ('=', '+'), ('[', '{'),
+	(']', '}'), ('\\', '|'), (';', ':'), ('\'', '"'), (',', '<'), ('.', '>'), ('/', '?'),
+];
+
+/// The character `key` types with shift, `None` if shift changes nothing
+pub fn shifted(key: char) -> Option<char> {
+	if key.is_ascii_lowercase() {
+		return Some(key.to_ascii_uppercase());
+	}
+	SHIFTED.iter().find(|(base, _)| *base == key).map(|(_, shifted)| *shifted)
+}
+
+/// The key typing `character` with shift, `None` if it is typed without
+pub fn unshifted(character: char) -> Option<char> {
+	if character.is_ascii_uppercase() {
+		return Some(character.to_ascii_lowercase());
+	}
+	SHIFTED.iter().find(|(_, shifted)| *shifted == character).map(|(base, _)| *base)
+}
+
 struct Rect {
 	width: u16,
 	height: u16,
@@ -32,36 +86,61 @@ struct Rect {
 	top: u16,
 }
 
-pub struct Render {
-	stdout: Stdout,
+pub struct Render<B: Backend> {
+	backend: B,
 	passage_rect: Rect,
+	theme: Theme,
+	messages: &'static Messages,
+	key_hint: String,
 }
 
-impl Render {
-	pub fn new() -> Self {
+impl<B: Backend> Render<B> {
+	pub fn new(backend: B, theme: Theme, messages: &'static Messages, key_hint: String) -> Self {
 		Self {
-			stdout: stdout(),
-			passage_rect: Self::calc_passage_rect(),
+			passage_rect: Self::calc_passage_rect(backend.size()),
+			backend,
+			theme,
+			messages,
+			key_hint,
 		}
 	}
+
+	pub fn theme(&self) -> &Theme {
+		&self.theme
+	}
+
+	pub fn backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Starts a new frame on a blank screen
 	pub fn refresh(&mut self) -> Result<(), io::Error> {
-		self.stdout.execute(terminal::Clear(terminal::ClearType::All))?;
+		self.backend.clear()?;
 		Ok(())
 	}
 
-	pub fn render_passage(&mut self, passage: &Passage, passed: usize, tone_on: ToneType) -> Result<(), io::Error> {
+	/// Shows what has been drawn since the last call
+	pub fn present(&mut self) -> Result<(), io::Error> {
+		self.backend.flush()?;
+		Ok(())
+	}
+
+	/// With `hide_tone`, the pinyin of the characters ahead goes without tone marks.
+	/// With `hide_hint`, the pinyin of the character being typed is left out.
+	/// With `word_spacing`, a column is left blank between words.
+	pub fn render_passage(&mut self, passage: &Passage, passed: usize, tone_on: ToneType, hide_tone: bool, hide_hint: bool, word_spacing: bool) -> Result<(), io::Error> {
 		self.render_passage_border()?;
 		let mut x = self.passage_rect.left;
 		let mut y = self.passage_rect.top;
 		let mut rendered_length = 0;
-		let lines = passage.wrap(self.passage_rect.width, tone_on == ToneType::Always);
+		let lines = passage.wrap(self.passage_rect.width, tone_on == ToneType::Always, word_spacing);
 
 		for line in lines.iter() {
 			let rendering_typing_line = rendered_length <= passed + 1&& rendered_length + line.len() > passed;
 			if rendering_typing_line {
-				self.stdout.
-					queue(MoveTo(x.saturating_sub(2), if tone_on == ToneType::Off {y} else {y + 1}))?.
-					queue(PrintStyledContent("┃".with(Color::Cyan)))?;
+				self.backend.
+					move_to(x.saturating_sub(2), if tone_on == ToneType::Off {y} else {y + 1})?.
+					print("┃".with(self.theme.cursor))?;
 			}
 
 			for (col, c) in line.iter().enumerate() {
@@ -70,39 +149,44 @@ impl Render {
 				match tone_on {
 					ToneType::Always => {
 						let mut char_width = c.char.width_cjk().unwrap_or(1);
-						let pinyin = c.pinyin_style(rendering_typing_line);
+						let [consonant, vowel] = c.pinyin_style(rendering_typing_line, hide_tone, &self.theme);
 						if !c.is_mark {
 							char_width = cmp::max(char_width, c.pinyin.as_ref().unwrap().pinyin.len() + 1);
 						}
-						self.stdout.
-						/*Pinyin*/	queue(&pinyin[0])?.
-						/*Pinyin*/	queue(&pinyin[1])?.
-						/* Text */	queue(MoveTo(x, y + 1))?.
-									queue(c.char_style(rendering_typing_line))?;
+						if !(hide_hint && rendered_length + col == passed + 1) {
+							self.backend.
+							/*Pinyin*/	print(consonant)?.
+							/*Pinyin*/	print(vowel)?;
+						}
+						self.backend.
+						/* Text */	move_to(x, y + 1)?.
+									print(c.char_style(rendering_typing_line, &self.theme))?;
 						x += char_width as u16;
 					},
 					ToneType::Live => {
 						let char_width = c.char.width_cjk().unwrap_or(1);
 						if rendering_typing_line {
-							if rendered_length + col == passed + 1 && !c.is_mark {
-								let pinyin = c.pinyin_style(rendering_typing_line);
-								self.stdout.
-								/*Pinyin*/	queue(&pinyin[0])?.
-								/*Pinyin*/	queue(&pinyin[1])?;
+							if rendered_length + col == passed + 1 && !c.is_mark && !hide_hint {
+								let [consonant, vowel] = c.pinyin_style(rendering_typing_line, hide_tone, &self.theme);
+								self.backend.
+								/*Pinyin*/	print(consonant)?.
+								/*Pinyin*/	print(vowel)?;
 							}
-							self.stdout.queue(MoveTo(x, y + 1))?;
+							self.backend.move_to(x, y + 1)?;
 						}
-						self.stdout.queue(c.char_style(rendering_typing_line))?;
+						self.backend.print(c.char_style(rendering_typing_line, &self.theme))?;
 						x += char_width as u16;
 					},
 					ToneType::Off => {
 						let char_width = c.char.width_cjk().unwrap_or(1);
-						self.stdout.queue(c.char_style(rendering_typing_line))?;
+						self.backend.print(c.char_style(rendering_typing_line, &self.theme))?;
 						x += char_width as u16;
 					},
 				}
+				if word_spacing && passage.word_gap(rendered_length + col) {
+					x += 1;
+				}
 			};
-			self.stdout.flush()?;
 			rendered_length += line.len();
 			x = self.passage_rect.left;
 			y += if rendering_typing_line {
@@ -119,34 +203,37 @@ impl Render {
 		h += 2;
 		y = y.saturating_sub(1);
 		x = x.saturating_sub(2);
-		self.stdout.
-			queue(MoveTo(x, y))?.
-			queue(PrintStyledContent(format!("{}{}{}", "┌", "─".repeat(w.into()), "┐").with(Color::DarkBlue)))?.
-			queue(MoveTo(x, y + h))?.
-			queue(PrintStyledContent(format!("{}{}{}", "└", "─".repeat(w.into()), "┘").with(Color::DarkBlue)))?.
-			queue(MoveTo(0, y + 1))?.
-			queue(PrintStyledContent(
-				format!(
-					"{}│{}│\n\r",
+		self.backend.
+			move_to(x, y)?.
+			print(format!("{}{}{}", "┌", "─".repeat(w.into()), "┐").with(self.theme.key_border))?.
+			move_to(x, y + h)?.
+			print(format!("{}{}{}", "└", "─".repeat(w.into()), "┘").with(self.theme.key_border))?;
+		for row in y + 1..y + h {
+			self.backend.
+				move_to(0, row)?.
+				print(format!(
+					"{}│{}│",
 					" ".repeat(x.into()),
 					" ".repeat(w.into())
-				).repeat((h-1).into()).with(Color::DarkBlue))
-			)?;
+				).with(self.theme.key_border))?;
+		}
 		Ok(())
 	}
 	fn goto(&mut self, x: u16, y: u16) -> Result<(), io::Error> {
-		self.stdout.execute(MoveTo(x, y))?;
+		self.backend.move_to(x, y)?;
 		Ok(())
 	}
 
-	fn calc_passage_rect() -> Rect {
-		let max_height = if terminal::size().unwrap().1 > 27 { 
-			terminal::size().unwrap().1.saturating_div(2) - PADDING_TOP
+	fn calc_passage_rect(size: (u16, u16)) -> Rect {
+		let max_height = if size.1 > 27 { 
+			size.1.saturating_div(2) - PADDING_TOP
 		}
 		else {
-			terminal::size().unwrap().1 - PADDING_TOP * 2
+			// Leaves room for the compact keyboard, or for the legend when even that does not fit
+			let keyboard = if size.1 > 20 { KEYBOARD_ROWS * 3 } else { KEYBOARD_ROWS };
+			size.1.saturating_sub(PADDING_TOP * 2 + keyboard)
 		};
-		let mut max_width = terminal::size().unwrap().0;
+		let mut max_width = size.0;
 		let scaled_aside;
 		if max_width > MAX_WIDTH + 8 {				// window is too wide
 			scaled_aside = (max_width - MAX_WIDTH) / 2;
@@ -169,90 +256,300 @@ impl Render {
 	}
 
 	pub fn update_passage_rect(&mut self) {
-		self.passage_rect = Self::calc_passage_rect();
+		self.passage_rect = Self::calc_passage_rect(self.backend.size());
 	}
 
-	pub fn render_keyboard(&mut self, keyboard: &Keyboard, highlight_rule: HashMap<String, Color>) -> Result<(), io::Error> {
-		let max_width = keyboard.layout.iter().fold(0, |max, row| {cmp::max(max, row.len())}) as u16;
-		let max_height = keyboard.layout.len() as u16;
-		let terminal_width = terminal::size().unwrap().0;
-		let terminal_height = terminal::size().unwrap().1;
-		// dbg!(max_width, terminal_width);
-		if terminal_width < max_width * 9 || terminal_height / 2 - PADDING_TOP < max_height * 4 {
-			return Ok(());
-		}
-		let x = (terminal_width.saturating_sub(max_width * 9)) / 2;
+	/// Draws the keys as large as the room below the passage allows: full keys, full keys
+	/// without the number row, compact keys, or a legend of one line per row, highlighting
+	/// the keys typing the characters of `highlight_rule` in each, with shift when they are
+	/// shifted
+	pub fn render_keyboard(&mut self, keyboard: &Keyboard, highlight_rule: HashMap<char, Color>) -> Result<(), io::Error> {
+		let rows = keyboard.layout.len() as u16;
+		let (terminal_width, terminal_height) = self.backend.size();
 		let y = self.passage_rect.top + self.passage_rect.height + PADDING_TOP;
-		let offset = 2;
-		self.goto(x, y)?;
-		keyboard.key_map.values().for_each(|key| {
-			key.render(&mut self.stdout, x + key.position.0 * 9 + key.position.1 * offset, y + key.position.1 * 4, highlight_rule.get(&key.name)).unwrap();
-		});
-		self.stdout.flush()?;
+		let height = terminal_height.saturating_sub(y);
+		// Width of the widest row from `first` with its keys and offset, and the closing border
+		let width = |key_width: u16, offset: u16, first: usize| keyboard.layout.iter().enumerate()
+			.skip(first)
+			.map(|(row, keys)| keys.len() as u16 * key_width + row as u16 * offset + 1)
+			.max()
+			.unwrap_or(0);
+		// The first row drawn with full keys, each row taking 4 lines and the last border 1
+		let full = (0..2).find(|&first| terminal_width >= width(9, 2, first) && height > (rows - first as u16) * 4);
+		if let Some(first) = full {
+			let x = (terminal_width - width(9, 2, first)) / 2;
+			let offset = 2;
+			self.render_key_borders(keyboard, x, y, offset, first)?;
+			for key in keyboard.key_map.values().filter(|key| key.position.1 as usize >= first) {
+				let row = key.position.1 - first as u16;
+				key.render(&mut self.backend, x + key.position.0 * 9 + key.position.1 * offset, y + row * 4, key.highlight(&highlight_rule), &self.theme)?;
+			}
+		}
+		else if terminal_width >= width(5, 1, 0) && height >= rows * 3 {
+			let x = (terminal_width - width(5, 1, 0)) / 2;
+			for (row, keys) in keyboard.layout.iter().enumerate() {
+				let row = row as u16;
+				for (col, name) in keys.iter().enumerate() {
+					let key = &keyboard.key_map[name];
+					key.render_compact(&mut self.backend, x + col as u16 * 5 + row, y + row * 3, key.highlight(&highlight_rule), &self.theme)?;
+				}
+				let right = x + keys.len() as u16 * 5 + row;
+				for line in 0..3 {
+					self.backend.
+						move_to(right, y + row * 3 + line)?.
+						print("│".with(self.theme.key_border))?;
+				}
+			}
+		}
+		else if height >= rows {
+			self.render_legend(keyboard, y, &highlight_rule)?;
+		}
+		Ok(())
+	}
+
+	/// The lines above, between and below the rows of full keys from `first`, `offset`
+	/// columns apart, joining the borders of the keys on either side
+	fn render_key_borders(&mut self, keyboard: &Keyboard, x: u16, y: u16, offset: u16, first: usize) -> Result<(), io::Error> {
+		// Left and right border of each row
+		let spans: Vec<(u16, u16)> = keyboard.layout.iter().enumerate()
+			.skip(first)
+			.map(|(row, keys)| (x + row as u16 * offset, x + row as u16 * offset + keys.len() as u16 * 9))
+			.collect();
+		let is_border = |span: Option<&(u16, u16)>, col: u16| span.is_some_and(|&(left, right)| col >= left && col <= right && (col - left).is_multiple_of(9));
+		let covers = |span: Option<&(u16, u16)>, col: u16| span.is_some_and(|&(left, right)| col >= left && col < right);
+		for line in 0..=spans.len() {
+			let above = line.checked_sub(1).and_then(|row| spans.get(row));
+			let below = spans.get(line);
+			let left = above.iter().chain(below.iter()).map(|span| span.0).min().unwrap_or(x);
+			let right = above.iter().chain(below.iter()).map(|span| span.1).max().unwrap_or(x);
+			let border: String = (left..=right).map(|col| {
+				let west = col > left && (covers(above, col - 1) || covers(below, col - 1));
+				let east = covers(above, col) || covers(below, col);
+				match (is_border(above, col), is_border(below, col), west, east) {
+					(false, true, false, _) => '┌',
+					(false, true, true, false) => '┐',
+					(false, true, true, true) => '┬',
+					(true, false, false, _) => '└',
+					(true, false, true, false) => '┘',
+					(true, false, true, true) => '┴',
+					(true, true, false, _) => '├',
+					(true, true, true, false) => '┤',
+					(true, true, true, true) => '┼',
+					(false, false, _, _) => '─',
+				}
+			}).collect();
+			self.backend.
+				move_to(left, y + line as u16 * 4)?.
+				print(border.with(self.theme.key_border))?;
+		}
+		Ok(())
+	}
+
+	/// One line for each row of keys from line `y`, each key followed by its initials and
+	/// finals, or the names alone if the terminal is too narrow for them
+	fn render_legend(&mut self, keyboard: &Keyboard, y: u16, highlight_rule: &HashMap<char, Color>) -> Result<(), io::Error> {
+		let terminal_width = self.backend.size().0 as usize;
+		let t = self.theme;
+		// A key and what is written for it, piece by piece
+		type Token<'k> = (&'k Key, Vec<(String, Color)>);
+		let rows: Vec<Vec<Token>> = keyboard.layout.iter().map(|keys| keys.iter().map(|name| {
+			let key = &keyboard.key_map[name];
+			let (initials, finals) = key.labels();
+			let shift = key.highlight(highlight_rule).is_some_and(|(_, shift)| shift);
+			let mut token = vec![(key.label(shift), t.key_name)];
+			for (i, (label, color)) in initials.iter().map(|label| (label, t.initial_label)).chain(finals.iter().map(|label| (label, t.final_label))).enumerate() {
+				token.push((String::from(if i == 0 { " " } else { "/" }), t.key_border));
+				token.push((label.to_string(), color));
+			}
+			(key, token)
+		}).collect()).collect();
+		let width = |row: &Vec<Token>, gap: usize| {
+			row.iter().map(|(_, token)| token.iter().map(|(s, _)| s.width()).sum::<usize>()).sum::<usize>() + row.len().saturating_sub(1) * gap
+		};
+		let (rows, gap) = if rows.iter().all(|row| width(row, 2) <= terminal_width) {
+			(rows, "  ")
+		}
+		else {
+			(rows.into_iter().map(|row| row.into_iter().map(|(key, token)| (key, token.into_iter().take(1).collect())).collect()).collect(), " ")
+		};
+		for (line, row) in rows.iter().enumerate() {
+			self.backend.move_to((terminal_width.saturating_sub(width(row, gap.len())) / 2) as u16, y + line as u16)?;
+			for (i, (key, token)) in row.iter().enumerate() {
+				if i > 0 {
+					self.backend.print(gap.with(t.key_border))?;
+				}
+				let highlight = key.highlight(highlight_rule);
+				if let Some((color, _)) = highlight {
+					self.backend.set_background(color)?;
+				}
+				for (s, color) in token {
+					self.backend.print(s.as_str().with(*color))?;
+				}
+				if highlight.is_some() {
+					self.backend.set_background(Color::Reset)?;
+				}
+			}
+		}
 		Ok(())
 	}
 
 	pub fn render_counter(&mut self, counter: &Counter) -> Result<(), io::Error> {
 		let interval = counter.get_interval().as_secs();
-		let terminal_width = terminal::size().unwrap().0;
-		let key_map_name = counter.get_key_map_name();
-		let typed_str = format!("  {}字", counter.get_typed_words());
-		let total_str = format!("{}字", counter.get_total_words());
-		let percent_str = format!("{:.1}%", counter.get_typed_words() as f32 / counter.get_total_words() as f32 * 100f32);
-		let mistakes_str = format!("{}", counter.get_mistakes());
+		let terminal_width = self.backend.size().0;
+		let m = self.messages;
+		let t = self.theme;
 		let speed_str =
 			if interval == 0 {
-				String::from("NaN字/min")
+				format!("NaN{}", m.speed_unit)
 			}
 			else {
-				format!("{}字/min", counter.get_typed_words() * 60u32 / interval as u32 )
+				format!("{}{}", counter.get_typed_words() * 60u32 / interval as u32, m.speed_unit)
 			};
 		let suggestions_str = {
 			if interval < 5 {
-				String::from("Press <C-q> to quit, <C-r> to try again")
+				self.key_hint.clone()
 			}
 			else {
 				String::new()
 			}
 		};
-		let time_str = format!("{:02}:{:02}  ", interval / 60, interval % 60);
-		let gap_length =
-			terminal_width as i32
-			- key_map_name.len() as i32
-			- typed_str.len() as i32
-			- total_str.len() as i32
-			- percent_str.len() as i32
-			- suggestions_str.len() as i32
-			- mistakes_str.len() as i32
-			- speed_str.len() as i32
-			- time_str.len() as i32
-			- LABEL_LEN;
+		let mut left = [
+			(format!("  {}{}", counter.get_typed_words(), m.char_unit), t.text),
+			(String::from("/"), t.text_muted),
+			(format!("{}{}", counter.get_total_words(), m.char_unit), t.text),
+			(String::from("│"), t.separator),
+			(format!("{:.1}%", counter.get_typed_words() as f32 / counter.get_total_words() as f32 * 100f32), t.text),
+			(String::from("│"), t.separator),
+			(counter.get_key_map_name(), t.text),
+			(String::from("│"), t.separator),
+			(suggestions_str, t.text),
+		];
+		let mut right = vec![
+			(m.mistake_label.to_string(), t.error_label),
+			(format!("{}", counter.get_mistakes()), t.error),
+			(String::from("│"), t.separator),
+			(m.speed_label.to_string(), t.text),
+			(speed_str, t.text),
+			(String::from("│"), t.separator),
+			(m.time_label.to_string(), t.text),
+			(if counter.is_started() { format!("{:02}:{:02}  ", interval / 60, interval % 60) } else { format!("{}  ", m.ready) }, t.text),
+		];
+		if let Some(tone_mistakes) = counter.get_tone_mistakes() {
+			right.splice(0..0, [
+				(m.tone_mistake_label.to_string(), t.error_label),
+				(format!("{}", tone_mistakes), t.error),
+				(String::from("│"), t.separator),
+			]);
+		}
+		let mut gap_length = terminal_width as i32
+			- left.iter().chain(right.iter()).map(|(s, _)| s.width() as i32).sum::<i32>();
+		if gap_length < 0 {
+			// Drop the suggestions before giving up on the whole bar
+			let suggestions = &mut left[left.len() - 1].0;
+			gap_length += suggestions.width() as i32;
+			suggestions.clear();
+		}
 		if gap_length < 0 {
 			return Ok(());
 		}
-		self.stdout.
-			queue(MoveTo(0,0))?.
-			queue(SetBackgroundColor(Color::Rgb { r: 42, g: 47, b: 49 }))?.
-			queue(PrintStyledContent(typed_str.with(Color::White)))?.
-			queue(PrintStyledContent("/".with(Color::Grey)))?.
-			queue(PrintStyledContent(total_str.with(Color::White)))?.
-			queue(PrintStyledContent("│".with(Color::Blue)))?.
-			queue(PrintStyledContent(percent_str.with(Color::White)))?.
-			queue(PrintStyledContent("│".with(Color::Blue)))?.
-			queue(PrintStyledContent(key_map_name.with(Color::White)))?.
-			queue(PrintStyledContent("│".with(Color::Blue)))?.
-			queue(PrintStyledContent(suggestions_str.with(Color::White)))?.
-			queue(PrintStyledContent(" ".repeat(gap_length.try_into().unwrap()).with(Color::White)))?.
-			queue(PrintStyledContent(MISTAKE_LABEL.with(Color::DarkRed)))?.
-			queue(PrintStyledContent(mistakes_str.with(Color::Red)))?.
-			queue(PrintStyledContent("│".with(Color::Blue)))?.
-			queue(PrintStyledContent(SPEED_LABEL.with(Color::White)))?.
-			queue(PrintStyledContent(speed_str.with(Color::White)))?.
-			queue(PrintStyledContent("│".with(Color::Blue)))?.
-			queue(PrintStyledContent(TIME_LABEL.with(Color::White)))?.
-			queue(PrintStyledContent(time_str.with(Color::White)))?.
-			queue(SetBackgroundColor(Color::Reset))?;
-		self.stdout.flush()?;
+		self.backend.
+			move_to(0,0)?.
+			set_background(t.status_bar)?;
+		for (s, color) in left {
+			self.backend.print(s.with(color))?;
+		}
+		self.backend.print(" ".repeat(gap_length.try_into().unwrap()).with(t.text))?;
+		for (s, color) in right {
+			self.backend.print(s.with(color))?;
+		}
+		self.backend.set_background(Color::Reset)?;
+		Ok(())
+	}
+
+	/// Hides the passage behind the pause notice
+	pub fn render_paused(&mut self) -> Result<(), io::Error> {
+		self.render_passage_border()?;
+		let Rect{width: w, height: h, top: y, left: x} = self.passage_rect;
+		let message = self.messages.paused;
+		self.backend.
+			move_to(x + (w.saturating_sub(message.width() as u16)) / 2, y + h / 2)?.
+			print(message.with(self.theme.hint))?;
+		Ok(())
+	}
+
+	/// Lists the initials and finals of every key, then the syllables without initial,
+	/// in as many columns as the terminal needs
+	pub fn render_key_map(&mut self, key_map: &KeyMap, keyboard: &Keyboard) -> Result<(), io::Error> {
+		self.refresh()?;
+		let (terminal_width, terminal_height) = self.backend.size();
+		let t = self.theme;
+		let m = self.messages;
+		let mut keys: Vec<char> = keyboard.layout.iter().flatten().copied().collect();
+		let mut others: Vec<char> = key_map.map.values().flatten().copied().filter(|key| !keys.contains(key)).collect();
+		others.sort_unstable();
+		others.dedup();
+		keys.extend(others);
+
+		let mut lines: Vec<Vec<(String, Color)>> = vec![
+			vec![(format!("{}/{}", m.initials_label, m.finals_label), t.title)],
+		];
+		for key in keys {
+			let phonemes = |initial: bool| {
+				let mut phonemes: Vec<&str> = key_map.map.iter()
+					.filter(|(phoneme, keys)| keys.contains(&key) && phoneme.as_str() != "er" && INITIALS.contains(&phoneme.as_str()) == initial)
+					.map(|(phoneme, _)| phoneme.as_str())
+					.collect();
+				phonemes.sort_unstable_by_key(|phoneme| (phoneme.len(), *phoneme));
+				phonemes.join(" ")
+			};
+			let (initials, finals) = (phonemes(true), phonemes(false));
+			if initials.is_empty() && finals.is_empty() {
+				continue;
+			}
+			lines.push(vec![
+				(format!("{}  ", key), t.key_name),
+				(initials.clone(), t.initial_label),
+				(String::from(if initials.is_empty() { "" } else { " " }), t.text),
+				(finals, t.final_label),
+			]);
+		}
+		lines.push(Vec::new());
+		lines.push(vec![(m.zero_initial_label.to_string(), t.title)]);
+		for vowel in ZERO_INITIAL_FINALS {
+			let pinyin = [String::new(), vowel.to_string()];
+			let join = |keys: Vec<char>| keys.iter().map(char::to_string).collect::<Vec<_>>().join("/");
+			let (first, second) = (key_map.keys(&pinyin, true), key_map.keys(&pinyin, false));
+			if first.is_empty() || second.is_empty() {
+				continue;
+			}
+			lines.push(vec![
+				(format!("{:<5}", vowel), t.final_label),
+				(format!("{} + {}", join(first), join(second)), t.key_name),
+			]);
+		}
+
+		let title = format!("{}{}", m.key_map_title, key_map.name());
+		let top = PADDING_TOP;
+		let height = terminal_height.saturating_sub(top + 4).max(1) as usize;
+		let width = lines.iter().map(|line| line.iter().map(|(s, _)| s.width()).sum::<usize>()).max().unwrap_or(0) + 4;
+		let columns = lines.len().div_ceil(height);
+		let left = (terminal_width as usize).saturating_sub(width * columns) / 2;
+		self.backend.
+			move_to(left as u16, top)?.
+			print(title.with(t.title))?;
+		for (i, line) in lines.into_iter().enumerate() {
+			let x = left + i / height * width;
+			if x + width > terminal_width as usize + 4 {
+				break;
+			}
+			self.backend.move_to(x as u16, top + 2 + (i % height) as u16)?;
+			for (s, color) in line {
+				self.backend.print(s.with(color))?;
+			}
+		}
+		self.backend.
+			move_to(left as u16, terminal_height.saturating_sub(2))?.
+			print(m.key_map_close.with(t.hint))?;
 		Ok(())
 	}
 
@@ -262,95 +559,137 @@ impl Render {
 		let x = self.passage_rect.left;
 		let y = self.passage_rect.top;
 		let w = self.passage_rect.width as usize;
+		let m = self.messages;
 		let interval = counter.get_interval().as_secs();
-		let mistakes_str = format!("错误: {}  ", counter.get_mistakes());
+		let mistakes_str = format!("{}{}", m.mistake_label, counter.get_mistakes());
 		let speed_str =
 			if interval == 0 {
-				String::from("速度: NaN字/min")
+				format!("{}NaN{}", m.speed_label, m.speed_unit)
 			}
 			else {
-				format!("速度: {}字/min", counter.get_typed_words() * 60u32 / interval as u32 )
+				format!("{}{}{}", m.speed_label, counter.get_typed_words() * 60u32 / interval as u32, m.speed_unit)
 			};
-		let time_str = format!("耗时: {:02}:{:02}  ", interval / 60, interval % 60);
-		self.stdout.
-			queue(MoveTo(x+2, y+1))?.
-			queue(PrintStyledContent(format!("{:<width$}", "🎉🎉 完成 🎉🎉", width=w-5).with(Color::Yellow)))?.
-			queue(MoveTo(x+2, y+3))?.
-			queue(PrintStyledContent(format!("{:<width$}", speed_str, width=w-2).with(Color::Cyan)))?.
-			queue(MoveTo(x+2, y+4))?.
-			queue(PrintStyledContent(format!("{:<width$}", time_str, width=w-2).with(Color::Cyan)))?.
-			queue(MoveTo(x+2, y+5))?.
-			queue(PrintStyledContent(format!("{:<width$}", mistakes_str, width=w-2).with(Color::Red)))?.
-			queue(MoveTo(x+2, y+7))?.
-			queue(PrintStyledContent(format!("{:<width$}", "Press <C-q> to quit, <C-r> to try again", width=w).with(Color::DarkYellow)))?.
-			flush()?;
+		let time_str = format!("{}{:02}:{:02}", m.time_label, interval / 60, interval % 60);
+		let reading = counter.get_reading_time().as_secs();
+		let reading_str = format!("{}{:02}:{:02}", m.reading_label, reading / 60, reading % 60);
+		let words = counter.get_word_stats();
+		let words_str = format!("{}{}{}{}", m.word_label, words.typed, m.clean_label, words.clean);
+		self.backend.
+			move_to(x+2, y+1)?.
+			print(pad(m.finished, w - 2).with(self.theme.title))?.
+			move_to(x+2, y+3)?.
+			print(pad(&speed_str, w - 2).with(self.theme.figure))?.
+			move_to(x+2, y+4)?.
+			print(pad(&time_str, w - 2).with(self.theme.figure))?.
+			move_to(x+2, y+5)?.
+			print(pad(&reading_str, w - 2).with(self.theme.figure))?.
+			move_to(x+2, y+6)?.
+			print(pad(&words_str, w - 2).with(self.theme.figure))?.
+			move_to(x+2, y+7)?.
+			print(pad(&mistakes_str, w - 2).with(self.theme.error))?;
+		let mut hint_y = y + 9;
+		if let Some(tone_mistakes) = counter.get_tone_mistakes() {
+			let tone_mistakes_str = format!("{}{}", m.tone_mistake_label, tone_mistakes);
+			self.backend.
+				move_to(x+2, y+8)?.
+				print(pad(&tone_mistakes_str, w - 2).with(self.theme.error))?;
+			hint_y += 1;
+		}
+		self.backend.
+			move_to(x+2, hint_y)?.
+			print(pad(&self.key_hint, w - 2).with(self.theme.hint))?;
 		Ok(())
 	}
 	
 }
 
-pub struct Keyboard<'a> {
-	key_map: HashMap<char, Key<'a>>,
-	layout: [Vec<char>;3],
+/// Pads `string` with spaces to `width` columns of the terminal
+fn pad(string: &str, width: usize) -> String {
+	format!("{}{}", string, " ".repeat(width.saturating_sub(string.width())))
+}
+
+impl Character {
+	pub fn char_style(&self, is_live: bool, theme: &Theme) -> StyledContent<char> {
+		if is_live {
+			match self.status {
+				CharStatus::Passed			=> self.char.with(theme.passed),
+				CharStatus::Typing			=> self.char.with(theme.typing),
+				CharStatus::TypingHalf		|
+				CharStatus::Uncommitted		=> self.char.with(theme.half_typed),
+				CharStatus::ErrorConsonant	|
+				CharStatus::ErrorVowel		|
+				CharStatus::ErrorMark	|
+				CharStatus::ErrorTone		=> self.char.with(theme.error),
+				CharStatus::Future			=> self.char.with(theme.future),
+			}
+		}
+		else {
+			match self.status {
+				CharStatus::Passed => self.char.with(theme.passed_dim),
+				CharStatus::Future => self.char.with(theme.future_dim),
+				/*Invalid*/ _ => self.char.with(theme.future_dim),
+			}
+		}
+	}
+
+	pub fn pinyin_style(&self, is_live: bool, hide_tone: bool, theme: &Theme) -> [StyledContent<String>;2] {
+		let pinyin = match self.pinyin.as_ref() {
+			None => String::new(),
+			Some(pinyin) if hide_tone && self.status != CharStatus::Passed => pinyin.pinyin.clone(),
+			Some(pinyin) => pinyin.pinyin_with_tone.clone(),
+		};
+		let consonant = if self.is_mark { String::new() } else { self.pinyin.as_ref().unwrap().pinyin_splitted[0].clone() };
+		let vowel = pinyin[consonant.len()..pinyin.len()].to_string();
+		if is_live {
+			match self.status {
+				/*🟩🟩*/ CharStatus::Passed			 => [consonant.with(theme.passed),	vowel.with(theme.passed).bold()],
+				/*🟦🟦*/ CharStatus::Typing			 => [consonant.with(theme.typing),	vowel.with(theme.typing).bold()],
+				/*🟩🟦*/ CharStatus::TypingHalf		 => [consonant.with(theme.passed),	vowel.with(theme.typing).bold()],
+				/*🟥🟦*/ CharStatus::ErrorConsonant	 => [consonant.with(theme.error),	vowel.with(theme.passed).bold()],
+				/*🟩🟥*/ CharStatus::ErrorVowel		 => [consonant.with(theme.passed),	vowel.with(theme.error).bold()],
+				/*🟥🟥*/ CharStatus::ErrorMark	 => [consonant.with(theme.error),	vowel.with(theme.error).bold()],
+				/*🟩🟥*/ CharStatus::ErrorTone		 => [consonant.with(theme.passed),	vowel.with(theme.error).bold()],
+				/*🟩🟩*/ CharStatus::Uncommitted	 => [consonant.with(theme.passed),	vowel.with(theme.passed).bold()],
+				/*⬜⬜*/ CharStatus::Future		   => [consonant.with(theme.future),  vowel.with(theme.future).bold()],
+			}
+		}
+		else {
+			match self.status {
+				/*🟩🟩*/ CharStatus::Passed	=> [consonant.with(theme.passed_dim),	vowel.with(theme.passed_dim).bold()],
+				/*⬜⬜*/ CharStatus::Future => [consonant.with(theme.future_dim),  vowel.with(theme.future_dim).bold()],
+				/*Invalid*/ _ => [consonant.with(theme.future_dim),  vowel.with(theme.future_dim)],
+			}
+		}
+	}
 }
 
-impl<'a> Keyboard<'a> {
-	pub fn new(key_map: &'a KeyMap, layout:[Vec<char>;3]) -> Self {
+pub struct Keyboard {
+	key_map: HashMap<char, Key>,
+	layout: [Vec<char>;4],
+}
 
+impl Keyboard {
+	pub fn new(key_map: &KeyMap, layout: [Vec<char>;4]) -> Self {
 		Self {
-			key_map: Self::reverse_mapping(key_map, layout.clone()),
+			key_map: Self::reverse_mapping(key_map, &layout),
 			layout,
 		}
 	}
 
-	pub fn default(key_map: &'a KeyMap) -> Self {
-		Self::new(key_map, [
-			vec!['q','w','e','r','t','y','u','i','o','p'],
-			 vec!['a','s','d','f','g','h','j','k','l',';'],
-			  vec!['z','x','c','v','b','n','m']
-		])
+	/// Column and row of `key`, `None` if it is not on the keyboard
+	pub fn position(&self, key: char) -> Option<(u16, u16)> {
+		self.key_map.get(&key).map(|key| key.position)
 	}
 
-	fn reverse_mapping(key_map: &KeyMap, layout: [Vec<char>;3]) -> HashMap<char, Key> {
-		let map = &key_map.map;
-		let mut reversed_key_map:HashMap<char, Vec<String>> = HashMap::new();
+	fn reverse_mapping(key_map: &KeyMap, layout: &[Vec<char>;4]) -> HashMap<char, Key> {
+		let reversed_key_map = key_map.reverse();
 		let mut result: HashMap<char, Key> = HashMap::new();
-		map.iter().for_each(|(phoneme, keys)| {
-			keys.iter().for_each(|key| {
-				if let Some(v) = reversed_key_map.get_mut(key) {
-					v.push(phoneme.to_string());
-				}
-				else {
-					reversed_key_map.insert(*key, vec![phoneme.to_string()]);
-				}
-			})
-		});
 		for (y, row) in layout.iter().enumerate() {
 			for (x, key) in row.iter().enumerate() {
-				let right = row.len() - 1;
-				let bottom = layout.len() - 1;
-				let key_border_top = 
-					if y == 0 && x == right  {key_border::top::NE} 
-					else if y == 0 && x == 0 {key_border::top::NW} 
-					else if y == 0 			 {key_border::top::N } 
-					else if x == right 		 {key_border::top::E }
-					else if x == 0			 {key_border::top::W } 
-					else 					 {key_border::top::CT};
-				let key_border_bottom =
-					if (y == bottom && x == right)
-					|| (y != bottom && x > layout.get(y + 1).unwrap().len()) {key_border::bottom::SE}
-					else if y == bottom && x == 0 {key_border::bottom::SW}
-					else if y == bottom 		  {key_border::bottom::S }
-					else if x == right			  {key_border::bottom::E }
-					else if x == 0				  {key_border::bottom::W }
-					else 						  {key_border::bottom::CT};
-				dbg!(key);
 				result.insert(
 					*key,
 					Key::new(
-						key_border_top,
-						key_border_bottom,
-						key.to_string(),
+						*key,
 						reversed_key_map.get(key).unwrap_or(&Vec::new()).clone(),
 						(x as u16, y as u16)
 					),
@@ -361,94 +700,263 @@ impl<'a> Keyboard<'a> {
 	}
 }
 
-struct Key<'a> {
-	top: &'a str,
-	bottom: &'a str,
+struct Key {
 	name: String,
+	/// What the key types with shift
+	shifted: Option<char>,
 	phonemes: Vec<String>,
 	position: (u16, u16),
 }
 
-impl<'a> Key<'a> {
-	pub fn new(top: &'a str, bottom: &'a str, name: String, phonemes: Vec<String>, position: (u16, u16)) -> Self {
-		Self {top, bottom, name, phonemes, position}
-	}
-
-	pub fn render(&self, stdout: &mut Stdout, x: u16, y: u16, highlight: Option<&Color>) -> Result<(), io::Error> {
-		stdout.
-		queue(MoveTo(x, y))?.
-		queue(PrintStyledContent(self.top.with(Color::DarkBlue)))?.
-			queue(MoveTo(x+1, y+1))?.
-			queue(PrintStyledContent(format!(" {:<7}", self.name).with(Color::Green)))?.
-			queue(MoveTo(x, y+1))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x+9, y+1))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x, y+2))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x+9, y+2))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x, y+3))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x+9, y+3))?.
-			queue(PrintStyledContent("│".with(Color::DarkBlue)))?.
-			queue(MoveTo(x, y+4))?.
-			queue(PrintStyledContent(self.bottom.with(Color::DarkBlue)))?;
+impl Key {
+	pub fn new(key: char, phonemes: Vec<String>, position: (u16, u16)) -> Self {
+		Self {
+			name: key.to_string(),
+			shifted: shifted(key),
+			phonemes,
+			position,
+		}
+	}
+
+	/// The color of the key in `highlight_rule`, by its own character or its shifted one,
+	/// and whether shift is to be held
+	fn highlight(&self, highlight_rule: &HashMap<char, Color>) -> Option<(Color, bool)> {
+		let key = self.name.chars().next()?;
+		highlight_rule.get(&key).map(|color| (*color, false))
+			.or_else(|| self.shifted.and_then(|shifted| highlight_rule.get(&shifted)).map(|color| (*color, true)))
+	}
+
+	/// The name of the key, as `S-x` when shift is to be held
+	fn label(&self, shift: bool) -> String {
+		if shift { format!("S-{}", self.name) } else { self.name.clone() }
+	}
 
+	/// The shifted character shown beside the name, for the keys that are not letters
+	fn shifted_label(&self) -> Option<char> {
+		self.shifted.filter(|shifted| !shifted.is_ascii_uppercase())
+	}
+
+	/// Draws the sides and the inside of the key, the lines above and below being drawn for the whole row
+	pub fn render<B: Backend>(&self, backend: &mut B, x: u16, y: u16, highlight: Option<(Color, bool)>, theme: &Theme) -> Result<(), io::Error> {
+		for line in 1..4 {
+			backend.
+				move_to(x, y + line)?.
+				print("│".with(theme.key_border))?.
+				move_to(x+9, y + line)?.
+				print("│".with(theme.key_border))?;
+		}
+		if let Some((color, _)) = highlight {
+			backend.set_background(color)?;
+		}
+		let shift = highlight.is_some_and(|(_, shift)| shift);
+		backend.
+			move_to(x+1, y+1)?.
+			print(format!(" {:<7}", self.label(shift)).with(theme.key_name))?.
+			move_to(x+1, y+2)?.
+			print("        ".with(theme.key_border))?.
+			move_to(x+1, y+3)?.
+			print("        ".with(theme.key_border))?;
+		if let Some(shifted) = self.shifted_label() {
+			backend.
+				move_to(x+7, y+1)?.
+				print(shifted.with(theme.key_name))?;
+		}
+		let (initials, finals) = self.labels();
+		for (i, initial) in initials.iter().enumerate() {
+			backend.
+				move_to(x+1, y + 3 - i as u16)?.
+				print(format!(" {}", initial).with(theme.initial_label))?;
+		}
+		for (i, vowel) in finals.iter().enumerate() {
+			backend.
+				move_to(x+8-vowel.width() as u16, y + 3 - i as u16)?.
+				print(vowel.to_string().with(theme.final_label))?;
+		}
+		if highlight.is_some() {
+			backend.set_background(Color::Reset)?;
+		}
+		Ok(())
+	}
 
+	/// Draws the key in 5 columns and 3 lines, with only its left border: the name and
+	/// an initial on the first line, the finals below it
+	pub fn render_compact<B: Backend>(&self, backend: &mut B, x: u16, y: u16, highlight: Option<(Color, bool)>, theme: &Theme) -> Result<(), io::Error> {
+		for line in 0..3 {
+			backend.
+				move_to(x, y + line)?.
+				print("│".with(theme.key_border))?;
+		}
+		if let Some((color, _)) = highlight {
+			backend.set_background(color)?;
+		}
+		let name = self.label(highlight.is_some_and(|(_, shift)| shift));
+		backend.
+			move_to(x+1, y)?.
+			print(format!("{:<4}", name).with(theme.key_name))?.
+			move_to(x+1, y+1)?.
+			print("    ".with(theme.key_border))?.
+			move_to(x+1, y+2)?.
+			print("    ".with(theme.key_border))?;
+		let (initials, finals) = self.labels();
+		// The first line takes an initial, or else a third final or the shifted character, if it fits after the name
+		let mut first_line = initials.first().map(|initial| (initial.to_string(), theme.initial_label));
+		for (i, vowel) in finals.iter().enumerate() {
+			if i < 2 {
+				backend.
+					move_to(x+5-vowel.width() as u16, y + 2 - i as u16)?.
+					print(vowel.to_string().with(theme.final_label))?;
+			}
+			else if first_line.is_none() {
+				first_line = Some((vowel.to_string(), theme.final_label));
+			}
+		}
+		if first_line.is_none() {
+			first_line = self.shifted_label().map(|shifted| (shifted.to_string(), theme.key_name));
+		}
+		if let Some((label, color)) = first_line.filter(|(label, _)| name.width() + 1 + label.width() <= 4) {
+			backend.
+				move_to(x+5-label.width() as u16, y)?.
+				print(label.with(color))?;
+		}
 		if highlight.is_some() {
-			stdout.queue(SetBackgroundColor(*highlight.unwrap()))?;
-		}
-		stdout.
-			queue(MoveTo(x+1, y+1))?.
-			queue(PrintStyledContent(format!(" {:<7}", self.name).with(Color::Green)))?.
-			queue(MoveTo(x+1, y+2))?.
-			queue(PrintStyledContent("        ".with(Color::DarkBlue)))?.
-			queue(MoveTo(x+1, y+3))?.
-			queue(PrintStyledContent("        ".with(Color::DarkBlue)))?;
-		let mut right_count = 0;
-		let mut left_count = 0;
+			backend.set_background(Color::Reset)?;
+		}
+		Ok(())
+	}
+
+	/// The initials shown on the key and its finals, shortest first. A key typing its own
+	/// letter among other phonemes leaves the letter out, but for the single vowels.
+	fn labels(&self) -> (Vec<&str>, Vec<&str>) {
+		let mut initials = Vec::new();
+		let mut finals = Vec::new();
 		for phoneme in self.phonemes.iter() {
-			if *phoneme == self.name && self.phonemes.len() > 1 && phoneme != "a" && phoneme != "e" && phoneme != "i" && phoneme != "o" && phoneme != "u"{
+			if *phoneme == self.name && self.phonemes.len() > 1 && !["a", "e", "i", "o", "u"].contains(&phoneme.as_str()) {
 				continue;
 			}
 			match phoneme.as_str() {
-				"zh" | "sh" | "ch" => {
-					stdout.
-						queue(MoveTo(x+1, y + 3 - left_count))?.
-						queue(PrintStyledContent(format!(" {}", phoneme).with(Color::Red)))?;
-					left_count += 1;
-				},
-				_ => {
-					stdout.
-						queue(MoveTo(x+8-phoneme.width() as u16, y + 3 - right_count))?.
-						queue(PrintStyledContent(phoneme.clone().with(Color::Yellow)))?;
-					right_count += 1;
-				}
+				"zh" | "sh" | "ch" => initials.push(phoneme.as_str()),
+				_ => finals.push(phoneme.as_str()),
 			}
-		};
-		if highlight.is_some() {
-			stdout.queue(SetBackgroundColor(Color::Reset))?;
 		}
-		Ok(())
+		initials.sort_unstable_by_key(|phoneme| (phoneme.len(), *phoneme));
+		finals.sort_unstable_by_key(|phoneme| (phoneme.len(), *phoneme));
+		(initials, finals)
 	}
 }
 
-mod key_border{
-	pub mod top {
-		pub const NW :&str = "┌────────┬";
-		pub const N	 :&str = "┬────────┬";
-		pub const NE :&str = "┬────────┐";
-		pub const CT :&str = "┬──────┴─┬";
-		pub const W  :&str = "┬──────┴─┬";
-		pub const E	 :&str = "┬──────┴─┐";
-	}
-	pub mod bottom {
-		pub const SW :&str = "└────────┴";
-		pub const S  :&str = "┴────────┴";
-		pub const SE :&str = "┴────────┘";
-		pub const CT :&str = "┴─┬──────┴";
-		pub const W  :&str = "└─┬──────┴";
-		pub const E  :&str = "┴─┬──────┘";
-	} 
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{backend::Grid, theme::DARK, i18n::ZH_CN, session::{Session, Rules}};
+
+	fn render(width: u16, height: u16) -> Render<Grid> {
+		Render::new(Grid::new(width, height), DARK, &ZH_CN, String::from("hint"))
+	}
+
+	/// Where `text` starts on screen, wide characters taking two cells
+	fn find(grid: &Grid, text: &str) -> Option<(u16, u16)> {
+		let (width, height) = grid.size();
+		for y in 0..height {
+			for x in 0..width {
+				let mut cells = (x..width).filter_map(|x| grid.cell(x, y).unwrap().symbol);
+				if text.chars().all(|c| cells.next() == Some(c)) {
+					return Some((x, y));
+				}
+			}
+		}
+		None
+	}
+
+	#[test]
+	fn passage_shows_the_pinyin_of_the_character_being_typed() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = Session::new(Passage::new(String::from("你好。")), &key_map, Rules::default());
+		session.feed('n');
+		session.feed('i');
+		let mut render = render(120, 40);
+		render.render_passage(session.passage(), session.passed(), ToneType::Live, false, false, false).unwrap();
+		let grid = render.backend();
+		let (x, y) = find(grid, "你好。").unwrap();
+		assert_eq!(find(grid, "hǎo"), Some((x + 2, y - 1)));
+		assert_eq!(grid.cell(x, y).unwrap().foreground, DARK.passed);
+		assert_eq!(grid.cell(x + 2, y).unwrap().foreground, DARK.typing);
+		assert_eq!(grid.cell(x + 4, y).unwrap().foreground, DARK.future);
+		assert_eq!(grid.cell(x - 3, y).unwrap().symbol, Some('┃'));
+	}
+
+	#[test]
+	fn passage_spaces_words() {
+		let mut render = render(120, 40);
+		render.render_passage(&Passage::new(String::from("我们研究生命。")), 0, ToneType::Off, false, false, true).unwrap();
+		assert!(find(render.backend(), "我们 研究 生命。").is_some());
+	}
+
+	#[test]
+	fn keyboard_highlights_the_next_keys() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let keyboard = Keyboard::new(&key_map, LayoutType::Qwerty.rows());
+		let mut render = render(140, 40);
+		render.render_keyboard(&keyboard, HashMap::from([('q', DARK.highlight)])).unwrap();
+		let grid = render.backend();
+		let (x, y) = find(grid, " q ").unwrap();
+		assert_eq!(grid.cell(x + 1, y).unwrap().background, DARK.highlight);
+		assert_eq!(grid.cell(x, y - 1).unwrap().symbol, Some('─'));
+		assert!(find(grid, "iu").is_some());
+		let (x, y) = find(grid, " w ").unwrap();
+		assert_eq!(grid.cell(x + 1, y).unwrap().background, Color::Reset);
+	}
+
+	#[test]
+	fn keyboard_shrinks_on_small_terminals() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let keyboard = Keyboard::new(&key_map, LayoutType::Qwerty.rows());
+		let mut full = render(140, 40);
+		full.render_keyboard(&keyboard, HashMap::new()).unwrap();
+		let mut letters = render(140, 30);
+		letters.render_keyboard(&keyboard, HashMap::new()).unwrap();
+		let mut compact = render(80, 24);
+		compact.render_keyboard(&keyboard, HashMap::new()).unwrap();
+		let mut legend = render(60, 18);
+		legend.render_keyboard(&keyboard, HashMap::new()).unwrap();
+		let rows = |render: &Render<Grid>| (0..render.backend().size().1).filter(|y| render.backend().line(*y).contains('q')).count();
+		assert!(find(full.backend(), " q ").is_some());
+		assert!(find(full.backend(), " 1 ").is_some());
+		// Full keys without the number row rather than compact keys
+		assert!(find(letters.backend(), " q ").is_some());
+		assert!(find(letters.backend(), " 1 ").is_none());
+		assert!(find(compact.backend(), "q").is_some());
+		assert!(find(legend.backend(), "q ").is_some());
+		assert_eq!(rows(&legend), 1);
+	}
+
+	#[test]
+	fn counter_fills_the_status_bar() {
+		let counter = Counter::new(12, String::from("微软双拼"));
+		let mut render = render(120, 40);
+		render.render_counter(&counter).unwrap();
+		let grid = render.backend();
+		assert!(grid.line(0).starts_with("  0字/12字│0.0%│微软双拼│hint"));
+		assert!(grid.line(0).ends_with("错误: 0│速度: NaN字/min│耗时: 准备"));
+		assert_eq!(grid.cell(0, 0).unwrap().background, DARK.status_bar);
+		let (x, y) = find(grid, "错误").unwrap();
+		assert_eq!(grid.cell(x, y).unwrap().foreground, DARK.error_label);
+	}
+
+	#[test]
+	fn summary_lists_the_results() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = Session::new(Passage::new(String::from("他说")), &key_map, Rules::default());
+		for key in ['t', 'x', 'a', 'u', 'o'] {
+			session.feed(key);
+		}
+		assert!(session.is_end());
+		let mut render = render(120, 40);
+		render.render_summary(session.counter()).unwrap();
+		let grid = render.backend();
+		let (x, y) = find(grid, "🎉🎉 完成 🎉🎉").unwrap();
+		assert_eq!(find(grid, "词语: 2, 无误 1"), Some((x, y + 5)));
+		assert_eq!(find(grid, "错误: 1"), Some((x, y + 6)));
+		assert_eq!(grid.cell(x, y + 6).unwrap().foreground, DARK.error);
+		assert_eq!(find(grid, "hint"), Some((x, y + 8)));
+	}
+}
@@ -0,0 +1,196 @@
+use std::{io, time::{SystemTime, UNIX_EPOCH}, collections::HashMap};
+
+use rand::seq::SliceRandom;
+
+use crate::{pin::{Document, Passage}, store::{Row, Store, Table}};
+
+const FILE: &str = "review.tsv";
+
+/// Characters in a review passage at most
+const REVIEW_SIZE: usize = 40;
+/// Syllables a review passage brings in for the first time at most
+const NEW_SIZE: usize = 10;
+
+/// The SM-2 state of a syllable
+#[derive(Debug, Clone, Copy)]
+struct Card {
+	ease: f32,
+	/// Days
+	interval: u32,
+	/// Right answers in a row since the last lapse
+	repetitions: u32,
+	/// Days since the Unix epoch
+	due: u64,
+}
+
+impl Row for Card {
+	fn parse(fields: &[&str]) -> Option<Self> {
+		match fields {
+			[ease, interval, repetitions, due] => Some(Self {
+				ease: ease.parse().ok()?,
+				interval: interval.parse().ok()?,
+				repetitions: repetitions.parse().ok()?,
+				due: due.parse().ok()?,
+			}),
+			_ => None,
+		}
+	}
+
+	fn fields(&self) -> String {
+		format!("{:.2}\t{}\t{}\t{}", self.ease, self.interval, self.repetitions, self.due)
+	}
+}
+
+/// When each syllable is to be reviewed, as kept in `review.tsv`
+pub struct Schedule {
+	cards: Table<Card>,
+}
+
+impl Schedule {
+	pub fn load(scheme: &str) -> Result<Self, io::Error> {
+		Ok(Self { cards: Table::load(Store::data(), FILE, scheme)? })
+	}
+
+	/// Writes back every scheme, `scheme syllable ease interval repetitions due` separated by tabs
+	pub fn save(&self) -> Result<(), io::Error> {
+		self.cards.save()
+	}
+
+	/// Grades an answer for `syllable` from 0 to 5 as SM-2 does, 3 and above being right.
+	/// A syllable seen for the first time is due at once; one that is not due yet only
+	/// moves on a wrong answer.
+	pub fn grade(&mut self, syllable: &str, quality: u8, today: u64) {
+		let card = self.cards.get_or_insert_with(syllable, || Card {
+			ease: 2.5,
+			interval: 0,
+			repetitions: 0,
+			due: today,
+		});
+		if quality >= 3 && card.due > today {
+			return;
+		}
+		if quality < 3 {
+			card.repetitions = 0;
+			card.interval = 1;
+		}
+		else {
+			card.interval = match card.repetitions {
+				0 => 1,
+				1 => 6,
+				_ => (card.interval as f32 * card.ease).round() as u32,
+			};
+			card.repetitions += 1;
+		}
+		let lapse = (5 - quality.min(5)) as f32;
+		card.ease = (card.ease + 0.1 - lapse * (0.08 + lapse * 0.02)).max(1.3);
+		card.due = today + card.interval as u64;
+	}
+
+	/// Whether `syllable` has ever been graded
+	pub fn knows(&self, syllable: &str) -> bool {
+		self.cards.get(syllable).is_some()
+	}
+
+	/// Syllables of the scheme due by `today`, the most overdue first
+	pub fn due(&self, today: u64) -> Vec<&str> {
+		let mut due: Vec<(&str, u64)> = self.cards.iter()
+			.filter(|(_, card)| card.due <= today)
+			.map(|(syllable, card)| (syllable, card.due))
+			.collect();
+		due.sort_unstable_by_key(|&(syllable, due)| (due, syllable));
+		due.into_iter().map(|(syllable, _)| syllable).collect()
+	}
+}
+
+/// How well a character was typed, for `Schedule::grade`: any mistake makes it a wrong answer
+pub fn quality(mistakes: u32) -> u8 {
+	match mistakes {
+		0 => 5,
+		1 => 2,
+		_ => 1,
+	}
+}
+
+/// Days since the Unix epoch
+pub fn today() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs() / 86400).unwrap_or_default()
+}
+
+/// A passage of characters from `document` carrying the syllables due by `today`, then up to
+/// `NEW_SIZE` syllables the schedule does not know yet, the most common in the document
+/// first, in random order. `None` if no character of the document reads as any of them.
+pub fn passage(document: &Document, schedule: &Schedule, today: u64) -> Option<Passage> {
+	let mut carriers: HashMap<&str, Vec<char>> = HashMap::new();
+	let mut counts: HashMap<&str, usize> = HashMap::new();
+	for character in document.passages.iter().flat_map(|passage| passage.chars.iter()) {
+		if let Some(pinyin) = character.pinyin.as_ref() {
+			*counts.entry(pinyin.pinyin.as_str()).or_default() += 1;
+			let chars = carriers.entry(pinyin.pinyin.as_str()).or_default();
+			if !chars.contains(&character.char) {
+				chars.push(character.char);
+			}
+		}
+	}
+	let mut new: Vec<(&str, usize)> = counts.into_iter().filter(|(syllable, _)| !schedule.knows(syllable)).collect();
+	new.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+	let mut rng = rand::thread_rng();
+	let mut chars: Vec<char> = schedule.due(today).into_iter()
+		.filter_map(|syllable| carriers.get(syllable).and_then(|chars| chars.choose(&mut rng)).copied())
+		.take(REVIEW_SIZE)
+		.collect();
+	let room = (REVIEW_SIZE - chars.len()).min(NEW_SIZE);
+	chars.extend(new.into_iter().take(room).filter_map(|(syllable, _)| carriers[syllable].choose(&mut rng).copied()));
+	if chars.is_empty() {
+		return None;
+	}
+	chars.shuffle(&mut rng);
+	Some(Passage::new(chars.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hanzi(passage: &Passage) -> String {
+		passage.chars.iter().filter(|character| character.pinyin.is_some()).map(|character| character.char).collect()
+	}
+
+	fn schedule() -> Schedule {
+		Schedule { cards: Table::new(Store::data(), FILE, "小鹤双拼") }
+	}
+
+	#[test]
+	fn answers_move_the_due_date() {
+		let mut schedule = schedule();
+		schedule.grade("an", quality(0), 100);
+		schedule.grade("ai", quality(1), 100);
+		assert_eq!(schedule.due(100), Vec::<&str>::new());
+		assert_eq!(schedule.due(101), ["ai", "an"]);
+		// Not due yet, a right answer changes nothing
+		schedule.grade("ai", quality(0), 100);
+		assert_eq!(schedule.due(101), ["ai", "an"]);
+		schedule.grade("an", quality(0), 101);
+		assert_eq!(schedule.due(106), ["ai"]);
+		assert_eq!(schedule.due(107), ["ai", "an"]);
+	}
+
+	#[test]
+	fn passages_bring_in_new_syllables() {
+		let document = Document { passages: vec![Passage::new(String::from("安安爱二"))] };
+		let mut schedule = schedule();
+		let first = passage(&document, &schedule, 100).unwrap();
+		let mut chars: Vec<char> = hanzi(&first).chars().collect();
+		chars.sort_unstable();
+		assert_eq!(chars, ['二', '安', '爱']);
+
+		for syllable in ["an", "ai", "er"] {
+			schedule.grade(syllable, quality(0), 100);
+		}
+		assert!(passage(&document, &schedule, 100).is_none());
+		schedule.grade("an", quality(0), 101);
+		schedule.grade("ai", quality(0), 101);
+		schedule.grade("er", quality(2), 101);
+		let due = passage(&document, &schedule, 102).unwrap();
+		assert_eq!(hanzi(&due), "二");
+	}
+}
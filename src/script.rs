@@ -0,0 +1,92 @@
+use std::{io, fmt::Write};
+
+use crossterm::event::{Event, KeyEvent, KeyCode, KeyModifiers};
+
+use crate::{pin::Document, keymap::KeyMap, input::{Input, Options, KeyBinding}, backend::Grid};
+
+const SCREEN_WIDTH: u16 = 120;
+const SCREEN_HEIGHT: u16 = 40;
+
+/// Parses a keystroke script
+///
+/// Every character is a key press, except:
+/// - whitespace, which is ignored so scripts can be laid out freely
+/// - `<...>`, a key in the notation of `KeyBinding::parse`, eg. `<C-r>` `<Space>` `<Esc>`
+/// - `<lt>`, a literal `<`
+/// - lines starting with `#`, which are comments
+pub fn parse(script: &str) -> Result<Vec<KeyEvent>, String> {
+	let mut events = Vec::new();
+	for (no, line) in script.lines().enumerate() {
+		if line.trim_start().starts_with('#') {
+			continue;
+		}
+		let mut chars = line.chars();
+		while let Some(c) = chars.next() {
+			if c.is_whitespace() {
+				continue;
+			}
+			if c != '<' {
+				let modifiers = if c.is_uppercase() { KeyModifiers::SHIFT } else { KeyModifiers::NONE };
+				events.push(KeyEvent::new(KeyCode::Char(c), modifiers));
+				continue;
+			}
+			let name: String = chars.by_ref().take_while(|c| *c != '>').collect();
+			if name == "lt" {
+				events.push(KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE));
+				continue;
+			}
+			let binding = KeyBinding::parse(&name).map_err(|err| format!("line {}: {}", no + 1, err))?;
+			events.push(KeyEvent::new(binding.code, binding.modifiers));
+		}
+	}
+	Ok(events)
+}
+
+/// Feeds `events` to a headless `Input` and reports the final state
+///
+/// A restart starts the same passage over; a quit stops reading the script.
+/// With `screen`, the final screen is appended to the report.
+pub fn run(document: &Document, passage: usize, key_map: &KeyMap, options: &Options, events: &[KeyEvent], screen: bool) -> Result<String, io::Error> {
+	let passage = document.passages.get(passage).ok_or_else(|| {
+		io::Error::new(io::ErrorKind::InvalidInput, format!("There are only {} passages", document.passages.len()))
+	})?;
+	let new_input = || Input::with_passage(passage.clone(), key_map, options, Grid::new(SCREEN_WIDTH, SCREEN_HEIGHT));
+	let mut input = new_input();
+	input.render_all()?;
+	for event in events {
+		input.handle_event(Event::Key(*event))?;
+		if input.is_quit() {
+			break;
+		}
+		if input.is_restart() {
+			input = new_input();
+			input.render_all()?;
+		}
+	}
+
+	let session = input.session();
+	let counter = session.counter();
+	let mut report = String::new();
+	writeln!(report, "keymap: {}", counter.get_key_map_name()).unwrap();
+	writeln!(report, "typed: {}/{}", counter.get_typed_words(), counter.get_total_words()).unwrap();
+	let words = counter.get_word_stats();
+	writeln!(report, "words: {}/{}, clean {}", words.typed, words.total, words.clean).unwrap();
+	writeln!(report, "mistakes: {}", counter.get_mistakes()).unwrap();
+	if let Some(tone_mistakes) = counter.get_tone_mistakes() {
+		writeln!(report, "tone mistakes: {}", tone_mistakes).unwrap();
+	}
+	writeln!(report, "finished: {}", session.is_end()).unwrap();
+	writeln!(report, "reading: {:.3}s", counter.get_reading_time().as_secs_f64()).unwrap();
+	writeln!(report, "time: {:.3}s", counter.get_interval().as_secs_f64()).unwrap();
+	writeln!(report).unwrap();
+	// The first character is the padding added by `Passage::new`
+	for c in session.passage().chars.iter().skip(1) {
+		let pinyin = c.pinyin.as_ref().map(|pinyin| pinyin.pinyin.as_str()).unwrap_or("-");
+		writeln!(report, "{}\t{}\t{}", c.char.escape_debug(), pinyin, c.status.name()).unwrap();
+	}
+	if screen {
+		writeln!(report).unwrap();
+		write!(report, "{}", input.backend()).unwrap();
+	}
+	Ok(report)
+}
@@ -0,0 +1,643 @@
+use std::{time::{Duration, Instant}, cmp};
+
+use crate::{pin::{Passage, Character, CharStatus}, keymap::KeyMap, punctuation::PunctuationMap};
+
+/// What a key did to the session
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Outcome {
+	/// A key of the character is right, and more are to come
+	HalfTyped,
+	/// The character is done, and the next one is waiting
+	Passed,
+	/// The last character is done
+	Finished,
+	/// Wrong key for the initial
+	WrongConsonant,
+	/// Wrong key for the final
+	WrongVowel,
+	/// Wrong key for a character typed with one key, eg. a letter or a punctuation mark
+	WrongMark,
+	/// Wrong key for the tone
+	WrongTone,
+	/// Another key than space after the last character of a word, when words are committed
+	WrongCommit,
+	/// The session has already finished
+	Ignored,
+}
+
+impl Outcome {
+	pub fn is_correct(&self) -> bool {
+		matches!(self, Outcome::HalfTyped | Outcome::Passed | Outcome::Finished)
+	}
+}
+
+/// What the user is practicing
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Mode {
+	/// Two keys per character, as defined by the `KeyMap`
+	#[default]
+	Shuangpin,
+	/// The whole pinyin, one key per letter, `ü` as `v`
+	Quanpin,
+}
+
+/// How the passage is typed
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+	pub mode: Mode,
+	/// The tone is typed after the pinyin, with the keys of `KeyMap::tone_keys`
+	pub tone: bool,
+	/// ASCII letters and digits are typed as they are, instead of being skipped like marks
+	pub literal: bool,
+	/// Chinese punctuation marks are typed with these keys, instead of being skipped
+	pub punctuation: Option<PunctuationMap>,
+	/// Each word is committed with space after its last character, as in an IME
+	pub commit: bool,
+}
+
+/// Typing one passage with one `KeyMap`, without any UI
+///
+/// ```
+/// use spcli::{KeyMap, Passage, Session, Outcome, session::Rules};
+///
+/// let key_map = KeyMap::open("./keymap/ms")?;
+/// let mut session = Session::new(Passage::new(String::from("你好")), &key_map, Rules::default());
+/// assert_eq!(session.feed('n'), Outcome::HalfTyped);
+/// assert_eq!(session.feed('i'), Outcome::Passed);
+/// assert_eq!(session.feed('x'), Outcome::WrongConsonant);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Session<'a> {
+	passage: Passage,
+	key_map: &'a KeyMap,
+	rules: Rules,
+	/// `passage.chars[passed + 1]` is the character being typed
+	passed: usize,
+	/// Keys of the character being typed that are done
+	typed_keys: usize,
+	/// The character being typed is done, and waits for the space committing its word
+	committing: bool,
+	counter: Counter,
+	end: bool,
+}
+
+impl<'a> Session<'a> {
+	pub fn new(passage: Passage, key_map: &'a KeyMap, rules: Rules) -> Self {
+		let total_words = passage.chars.iter().fold(0, |acc, char| { if rules.is_typed(char) { acc + 1 } else { acc } });
+		let name = rules.scheme(key_map);
+		let mut session = Self {
+			passage,
+			key_map,
+			rules,
+			passed: 0,
+			typed_keys: 0,
+			committing: false,
+			counter: Counter::new(total_words, name),
+			end: false,
+		};
+		if session.rules.tone {
+			session.counter.track_tones();
+		}
+		let words = session.passage.chars.iter().filter(|c| c.word_end && !c.is_mark).count();
+		session.counter.count_words(words as u32);
+		if session.skip_marks() {
+			session.set_typing_status(CharStatus::Typing);
+		}
+		else {
+			session.finish();
+		}
+		session
+	}
+
+	pub fn rules(&self) -> &Rules {
+		&self.rules
+	}
+
+	pub fn passage(&self) -> &Passage {
+		&self.passage
+	}
+
+	pub fn passed(&self) -> usize {
+		self.passed
+	}
+
+	/// The character being typed, `None` once the session is over
+	pub fn typing(&self) -> Option<&Character> {
+		if self.end { None } else { self.passage.chars.get(self.passed + 1) }
+	}
+
+	/// Whether the next key is the first one of the character
+	pub fn typing_consonant(&self) -> bool {
+		self.typed_keys == 0
+	}
+
+	pub fn counter(&self) -> &Counter {
+		&self.counter
+	}
+
+	pub fn is_end(&self) -> bool {
+		self.end
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.counter.is_paused()
+	}
+
+	/// Freezes the timer until `resume`
+	pub fn pause(&mut self) {
+		self.counter.pause();
+	}
+
+	pub fn pause_since(&mut self, since: Instant) {
+		self.counter.pause_since(since);
+	}
+
+	pub fn resume(&mut self) {
+		self.counter.resume();
+	}
+
+	/// Keys accepted next, empty once the session is over
+	pub fn next_keys(&self) -> Vec<char> {
+		if self.end {
+			return Vec::new();
+		}
+		if self.committing {
+			return vec![' '];
+		}
+		let typing = self.passage.chars.get(self.passed + 1).unwrap();
+		match typing.pinyin.as_ref() {
+			Some(pinyin) if self.typing_tone() => self.key_map.tone_keys(pinyin.tone),
+			Some(pinyin) if self.rules.mode == Mode::Quanpin => {
+				pinyin.pinyin.chars().nth(self.typed_keys).map(|letter| if letter == 'ü' { 'v' } else { letter }).into_iter().collect()
+			},
+			Some(pinyin) => self.key_map.keys(&pinyin.pinyin_splitted, self.typed_keys == 0),
+			None => match self.rules.punctuation.as_ref().and_then(|punctuation| punctuation.keys(typing.char)) {
+				Some(keys) => keys.clone(),
+				None => vec![typing.char],
+			},
+		}
+	}
+
+	pub fn feed(&mut self, key: char) -> Outcome {
+		if self.end {
+			return Outcome::Ignored;
+		}
+		self.resume();
+		self.counter.start();
+		let pass = self.next_keys().contains(&key);
+		if self.committing {
+			if pass {
+				self.committing = false;
+				self.pass()
+			}
+			else {
+				self.set_typing_status(CharStatus::ErrorMark);
+				self.counter.add_mistake();
+				Outcome::WrongCommit
+			}
+		}
+		else if self.passage.chars[self.passed + 1].is_mark {
+			// A literal or a punctuation mark, one key for the whole character
+			if pass {
+				self.pass()
+			}
+			else {
+				self.set_typing_status(CharStatus::ErrorMark);
+				self.counter.add_mistake();
+				Outcome::WrongMark
+			}
+		}
+		else if pass {
+			self.typed_keys += 1;
+			if self.typed_keys < self.keys_per_char() {
+				self.set_typing_status(CharStatus::TypingHalf);
+				Outcome::HalfTyped
+			}
+			else if self.rules.commit && self.passage.chars[self.passed + 1].word_end {
+				self.committing = true;
+				self.set_typing_status(CharStatus::Uncommitted);
+				Outcome::HalfTyped
+			}
+			else {
+				self.pass()
+			}
+		}
+		else if self.typing_tone() {
+			self.set_typing_status(CharStatus::ErrorTone);
+			self.counter.add_tone_mistake();
+			Outcome::WrongTone
+		}
+		else if self.typing_initial() {
+			self.set_typing_status(CharStatus::ErrorConsonant);
+			self.counter.add_mistake();
+			Outcome::WrongConsonant
+		}
+		else {
+			self.set_typing_status(CharStatus::ErrorVowel);
+			self.counter.add_mistake();
+			Outcome::WrongVowel
+		}
+	}
+
+	/// Keys it takes to type the character being typed, the tone included
+	fn keys_per_char(&self) -> usize {
+		self.pinyin_keys() + self.rules.tone as usize
+	}
+
+	/// Keys it takes to type the pinyin of the character being typed
+	fn pinyin_keys(&self) -> usize {
+		let pinyin = self.passage.chars[self.passed + 1].pinyin.as_ref().unwrap();
+		match self.rules.mode {
+			Mode::Shuangpin => 2,
+			Mode::Quanpin => pinyin.pinyin.chars().count(),
+		}
+	}
+
+	/// Whether the pinyin is done and the tone is next
+	fn typing_tone(&self) -> bool {
+		self.rules.tone && self.typed_keys == self.pinyin_keys()
+	}
+
+	/// Whether the next key belongs to the initial
+	fn typing_initial(&self) -> bool {
+		let pinyin = self.passage.chars[self.passed + 1].pinyin.as_ref().unwrap();
+		match self.rules.mode {
+			Mode::Shuangpin => self.typed_keys == 0,
+			Mode::Quanpin => self.typed_keys < pinyin.pinyin_splitted[0].len(),
+		}
+	}
+
+	/// Passes the character being typed and moves on to the next one
+	fn pass(&mut self) -> Outcome {
+		self.set_typing_status(CharStatus::Passed);
+		self.passed += 1;
+		self.counter.add_typed_words();
+		let character = &self.passage.chars[self.passed];
+		if character.word_end {
+			self.counter.end_word(!character.is_mark);
+		}
+		self.typed_keys = 0;
+		if self.skip_marks() {
+			self.set_typing_status(CharStatus::Typing);
+			Outcome::Passed
+		}
+		else {
+			self.finish();
+			Outcome::Finished
+		}
+	}
+
+	/// Passes the marks ahead, returns `false` if nothing is left to type
+	fn skip_marks(&mut self) -> bool {
+		loop {
+			match self.passage.chars.get_mut(self.passed + 1) {
+				Some(character) if !self.rules.is_typed(character) => {
+					character.set_status(CharStatus::Passed);
+					self.passed += 1;
+					if character.word_end {
+						self.counter.end_word(false);
+					}
+				},
+				Some(_) => return true,
+				None => return false,
+			}
+		}
+	}
+
+	fn set_typing_status(&mut self, status: CharStatus) {
+		self.passage.chars.get_mut(self.passed + 1).unwrap().set_status(status);
+	}
+
+	fn finish(&mut self) {
+		self.end = true;
+		self.counter.lock();
+	}
+}
+
+impl Rules {
+	/// The name of what is learnt, the keymap name or 全拼 for full pinyin
+	pub fn scheme(&self, key_map: &KeyMap) -> String {
+		match self.mode {
+			Mode::Shuangpin => key_map.name().to_string(),
+			Mode::Quanpin => String::from("全拼"),
+		}
+	}
+
+	/// Whether `character` has to be typed, rather than skipped
+	pub fn is_typed(&self, character: &Character) -> bool {
+		!character.is_mark
+			|| (self.literal && character.char.is_ascii_alphanumeric())
+			|| self.punctuation.as_ref().is_some_and(|punctuation| punctuation.keys(character.char).is_some())
+	}
+}
+
+/// Words of the passage as `words::segment` cuts it, those made of marks left out
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct WordStats {
+	pub total: u32,
+	pub typed: u32,
+	/// Words typed without a mistake, the tones and the commits included
+	pub clean: u32,
+}
+
+pub struct Counter {
+	total_words: u32,
+	typed_words: u32,
+	mistakes: u32,
+	/// `None` unless the tones are typed
+	tone_mistakes: Option<u32>,
+	words: WordStats,
+	/// Mistakes and tone mistakes when the last word was done
+	word_start_mistakes: u32,
+	/// When the passage was shown
+	shown_time: Instant,
+	/// When the first key was pressed
+	start_time: Option<Instant>,
+	time_locked: bool,
+	end_time: Instant,
+	/// Total of the finished pauses
+	paused: Duration,
+	/// Part of `paused` before the first key
+	paused_reading: Duration,
+	/// Start of the current pause
+	paused_since: Option<Instant>,
+	key_map_name: String,
+}
+
+impl Counter {
+	pub fn new(total_words: u32, key_map_name: String) -> Self {
+		Self {
+			total_words,
+			typed_words: 0,
+			mistakes: 0,
+			tone_mistakes: None,
+			words: WordStats::default(),
+			word_start_mistakes: 0,
+			shown_time: Instant::now(),
+			start_time: None,
+			end_time: Instant::now(),
+			time_locked: false,
+			paused: Duration::ZERO,
+			paused_reading: Duration::ZERO,
+			paused_since: None,
+			key_map_name,
+		}
+	}
+
+	pub fn add_typed_words(&mut self) {
+		self.typed_words += 1;
+	}
+
+	pub fn add_mistake(&mut self) {
+		self.mistakes += 1;
+	}
+
+	/// Counts tone mistakes from now on
+	pub fn track_tones(&mut self) {
+		self.tone_mistakes.get_or_insert(0);
+	}
+
+	pub fn add_tone_mistake(&mut self) {
+		if let Some(tone_mistakes) = self.tone_mistakes.as_mut() {
+			*tone_mistakes += 1;
+		}
+	}
+
+	/// Counts the words from now on, `total` of them in the passage
+	pub fn count_words(&mut self, total: u32) {
+		self.words = WordStats { total, ..WordStats::default() };
+	}
+
+	/// The last character of a word is passed, `typed` unless it is a mark. The word is
+	/// clean if no mistake was made since the word before.
+	pub fn end_word(&mut self, typed: bool) {
+		let mistakes = self.mistakes + self.tone_mistakes.unwrap_or(0);
+		if typed {
+			self.words.typed += 1;
+			if mistakes == self.word_start_mistakes {
+				self.words.clean += 1;
+			}
+		}
+		self.word_start_mistakes = mistakes;
+	}
+
+	pub fn get_word_stats(&self) -> WordStats {
+		self.words
+	}
+
+	/// Wrong tones, kept apart from `get_mistakes`
+	pub fn get_tone_mistakes(&self) -> Option<u32> {
+		self.tone_mistakes
+	}
+
+	pub fn get_total_words(&self) -> u32 {
+		self.total_words
+	}
+
+	pub fn get_typed_words(&self) -> u32 {
+		self.typed_words
+	}
+
+	pub fn get_mistakes(&self) -> u32 {
+		self.mistakes
+	}
+
+	pub fn get_key_map_name(&self) -> String {
+		self.key_map_name.clone()
+	}
+
+	/// Starts timing, if it hasn't started yet
+	pub fn start(&mut self) {
+		if self.start_time.is_none() {
+			self.resume();
+			self.start_time = Some(Instant::now());
+			self.paused_reading = self.paused;
+		}
+	}
+
+	pub fn is_started(&self) -> bool {
+		self.start_time.is_some()
+	}
+
+	/// Time spent typing, from the first key on, pauses excluded
+	pub fn get_interval(&self) -> Duration {
+		let start_time = match self.start_time {
+			Some(start_time) => start_time,
+			None => return Duration::ZERO,
+		};
+		let end = if self.time_locked { self.end_time } else { Instant::now() };
+		let paused = self.paused - self.paused_reading + self.paused_since.map(|since| end.saturating_duration_since(since)).unwrap_or_default();
+		end.duration_since(start_time).saturating_sub(paused)
+	}
+
+	/// Time spent reading before the first key, pauses excluded
+	pub fn get_reading_time(&self) -> Duration {
+		match self.start_time {
+			Some(start_time) => start_time.duration_since(self.shown_time).saturating_sub(self.paused_reading),
+			None => {
+				let paused = self.paused + self.paused_since.map(|since| since.elapsed()).unwrap_or_default();
+				self.shown_time.elapsed().saturating_sub(paused)
+			},
+		}
+	}
+
+	pub fn lock(&mut self) {
+		self.resume();
+		self.time_locked = true;
+		self.end_time = Instant::now();
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.paused_since.is_some()
+	}
+
+	pub fn pause(&mut self) {
+		self.pause_since(Instant::now());
+	}
+
+	/// Pauses as if it had happened at `since`, eg. at the last key press when the user went idle
+	pub fn pause_since(&mut self, since: Instant) {
+		if !self.time_locked && self.paused_since.is_none() {
+			self.paused_since = Some(cmp::max(since, self.start_time.unwrap_or(self.shown_time)));
+		}
+	}
+
+	pub fn resume(&mut self) {
+		if let Some(since) = self.paused_since.take() {
+			self.paused += Instant::now().saturating_duration_since(since);
+		}
+	}
+
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+
+	/// `er`, the syllables without initial, then two with an initial
+	const PASSAGE: &str = "二安昂欧爱饿你双女略";
+
+	fn start<'a>(text: &str, key_map: &'a KeyMap, rules: Rules) -> Session<'a> {
+		Session::new(Passage::new(text.to_string()), key_map, rules)
+	}
+
+	/// Feeds `keys`, spaces left out
+	fn feed_all(session: &mut Session, keys: &str) -> Vec<Outcome> {
+		keys.chars().filter(|key| *key != ' ').map(|key| session.feed(key)).collect()
+	}
+
+	#[test]
+	fn every_scheme_types_er_and_syllables_without_initial() {
+		let schemes = [
+			("++", "eq af ag op as ee ni ih nv lx"),
+			("abc", "er oj oh ob ol oe ni vt nv lm"),
+			("gb", "al af ag ap ak ae ni un nv lx"),
+			("ms", "er oj oh ob ol oe ni ud ny lv"),
+			("sg", "er oj oh ob ol oe ni ud ny lt"),
+			("xh", "er an ah ou ai ee ni ul nv lt"),
+			("zg", "er or os oz op oe ni ig nv ln"),
+			("zr", "er an ah ou ai ee ni ud nv lt"),
+		];
+		let mut files: Vec<String> = fs::read_dir("./keymap").unwrap()
+			.map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+			.collect();
+		files.sort_unstable();
+		assert_eq!(files, schemes.map(|(name, _)| name.to_string()));
+		for (name, keys) in schemes {
+			let key_map = KeyMap::open(&format!("./keymap/{}", name)).unwrap();
+			let mut session = start(PASSAGE, &key_map, Rules::default());
+			let outcomes = feed_all(&mut session, keys);
+			assert!(outcomes.iter().all(Outcome::is_correct), "{}: {:?}", name, outcomes);
+			assert_eq!(outcomes.last(), Some(&Outcome::Finished), "{}", name);
+			assert_eq!(session.counter().get_mistakes(), 0, "{}", name);
+		}
+	}
+
+	#[test]
+	fn er_takes_the_leader_keys_too_when_split() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		for keys in ["er", "or"] {
+			let mut session = start("二", &key_map, Rules::default());
+			assert_eq!(feed_all(&mut session, keys), [Outcome::HalfTyped, Outcome::Finished]);
+		}
+		// Explicit rules take over from the leader
+		let key_map = KeyMap::open("./keymap/xh").unwrap();
+		let mut session = start("昂", &key_map, Rules::default());
+		assert_eq!(feed_all(&mut session, "ag"), [Outcome::HalfTyped, Outcome::WrongVowel]);
+	}
+
+	#[test]
+	fn mistakes_are_told_apart_and_counted() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = start("你好", &key_map, Rules::default());
+		assert_eq!(session.feed('x'), Outcome::WrongConsonant);
+		assert_eq!(session.typing().unwrap().status, CharStatus::ErrorConsonant);
+		assert_eq!(session.feed('n'), Outcome::HalfTyped);
+		assert_eq!(session.feed('x'), Outcome::WrongVowel);
+		assert_eq!(session.typing().unwrap().status, CharStatus::ErrorVowel);
+		assert_eq!(session.next_keys(), ['i']);
+		assert_eq!(feed_all(&mut session, "ihk"), [Outcome::Passed, Outcome::HalfTyped, Outcome::Finished]);
+		assert_eq!(session.feed('h'), Outcome::Ignored);
+		assert_eq!(session.counter().get_mistakes(), 2);
+		assert_eq!(session.counter().get_typed_words(), 2);
+		assert_eq!(session.counter().get_tone_mistakes(), None);
+	}
+
+	#[test]
+	fn marks_are_skipped_unless_typed() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let session = start("A，你", &key_map, Rules::default());
+		assert_eq!(session.counter().get_total_words(), 1);
+		assert_eq!(session.typing().unwrap().char, '你');
+		let mut session = start("A，你", &key_map, Rules { literal: true, ..Rules::default() });
+		assert_eq!(feed_all(&mut session, "aAni"), [Outcome::WrongMark, Outcome::Passed, Outcome::HalfTyped, Outcome::Finished]);
+	}
+
+	#[test]
+	fn punctuation_is_typed_with_the_keys_of_the_map() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let punctuation = PunctuationMap::open("./punctuation/default").unwrap();
+		let mut session = start("你，好", &key_map, Rules { punctuation: Some(punctuation), ..Rules::default() });
+		assert_eq!(session.counter().get_total_words(), 3);
+		assert_eq!(feed_all(&mut session, "ni"), [Outcome::HalfTyped, Outcome::Passed]);
+		assert_eq!(session.typing().unwrap().char, '，');
+		assert_eq!(session.next_keys(), [',']);
+		assert_eq!(feed_all(&mut session, ".,hk"), [Outcome::WrongMark, Outcome::Passed, Outcome::HalfTyped, Outcome::Finished]);
+		assert_eq!(session.counter().get_mistakes(), 1);
+	}
+
+	#[test]
+	fn tones_are_typed_after_the_pinyin() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = start("你", &key_map, Rules { tone: true, ..Rules::default() });
+		assert_eq!(feed_all(&mut session, "ni2"), [Outcome::HalfTyped, Outcome::HalfTyped, Outcome::WrongTone]);
+		assert_eq!(session.feed('3'), Outcome::Finished);
+		assert_eq!(session.counter().get_mistakes(), 0);
+		assert_eq!(session.counter().get_tone_mistakes(), Some(1));
+	}
+
+	#[test]
+	fn full_pinyin_types_every_letter() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = start("女双", &key_map, Rules { mode: Mode::Quanpin, ..Rules::default() });
+		let outcomes = feed_all(&mut session, "nvshuang");
+		assert_eq!(outcomes.last(), Some(&Outcome::Finished));
+		assert!(outcomes.iter().all(Outcome::is_correct));
+	}
+
+	#[test]
+	fn words_are_committed_with_space() {
+		let key_map = KeyMap::open("./keymap/ms").unwrap();
+		let mut session = start("我们他", &key_map, Rules { commit: true, ..Rules::default() });
+		assert_eq!(feed_all(&mut session, "womf"), [Outcome::HalfTyped, Outcome::Passed, Outcome::HalfTyped, Outcome::HalfTyped]);
+		assert_eq!(session.typing().unwrap().status, CharStatus::Uncommitted);
+		assert_eq!(session.next_keys(), [' ']);
+		assert_eq!(session.feed('t'), Outcome::WrongCommit);
+		assert_eq!(session.feed(' '), Outcome::Passed);
+		assert_eq!(feed_all(&mut session, "ta"), [Outcome::HalfTyped, Outcome::HalfTyped]);
+		assert_eq!(session.feed(' '), Outcome::Finished);
+		assert_eq!(session.counter().get_word_stats(), WordStats { total: 2, typed: 2, clean: 1 });
+	}
+}
@@ -0,0 +1,166 @@
+use std::{io::{self, Write as _}, fs, path::PathBuf, fmt::Write, collections::HashMap};
+
+use crate::config;
+
+/// A directory keeping files of the user, the data directory but in tests
+#[derive(Debug, Clone)]
+pub struct Store {
+	/// `None` if there is no data directory
+	dir: Option<PathBuf>,
+}
+
+impl Store {
+	/// The data directory
+	pub fn data() -> Self {
+		Self { dir: config::data_dir() }
+	}
+
+	pub fn at(dir: PathBuf) -> Self {
+		Self { dir: Some(dir) }
+	}
+
+	/// `name` in the directory
+	pub fn path(&self, name: &str) -> Option<PathBuf> {
+		self.dir.as_ref().map(|dir| dir.join(name))
+	}
+
+	/// The content of `name`, empty when there is no such file
+	pub fn read(&self, name: &str) -> Result<String, io::Error> {
+		let path = match self.path(name) {
+			Some(path) => path,
+			None => return Ok(String::new()),
+		};
+		match fs::read_to_string(path) {
+			Ok(file) => Ok(file),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+			Err(err) => Err(err),
+		}
+	}
+
+	/// Replaces `name` with `content`, creating the directory if needed
+	pub fn write(&self, name: &str, content: &str) -> Result<(), io::Error> {
+		fs::write(self.create(name)?, content)
+	}
+
+	/// Adds `line` to the end of `name`
+	pub fn append(&self, name: &str, line: &str) -> Result<(), io::Error> {
+		let mut file = fs::OpenOptions::new().create(true).append(true).open(self.create(name)?)?;
+		writeln!(file, "{}", line)
+	}
+
+	fn create(&self, name: &str) -> Result<PathBuf, io::Error> {
+		let path = self.path(name).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No data directory"))?;
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		Ok(path)
+	}
+}
+
+/// What a `Table` keeps for a syllable, written as tab separated fields
+pub trait Row: Sized {
+	fn parse(fields: &[&str]) -> Option<Self>;
+	fn fields(&self) -> String;
+}
+
+impl Row for u32 {
+	fn parse(fields: &[&str]) -> Option<Self> {
+		match fields {
+			[field] => field.parse().ok(),
+			_ => None,
+		}
+	}
+
+	fn fields(&self) -> String {
+		self.to_string()
+	}
+}
+
+/// A file of a `Store` keeping a row per syllable and scheme, one
+/// `scheme syllable fields…` line each
+///
+/// The scheme is the keymap name, or 全拼 for full pinyin, as the codes to learn differ.
+/// Every scheme is kept, only those rows of `scheme` are read and updated. A missing file
+/// has no rows, broken lines are skipped.
+pub struct Table<T> {
+	store: Store,
+	name: &'static str,
+	scheme: String,
+	rows: HashMap<(String, String), T>,
+}
+
+impl<T: Row> Table<T> {
+	/// A table without rows, to be saved as `name` in `store`
+	pub fn new(store: Store, name: &'static str, scheme: &str) -> Self {
+		Self { store, name, scheme: scheme.to_string(), rows: HashMap::new() }
+	}
+
+	pub fn load(store: Store, name: &'static str, scheme: &str) -> Result<Self, io::Error> {
+		let file = store.read(name)?;
+		let mut table = Self::new(store, name, scheme);
+		for line in file.lines() {
+			if let [scheme, syllable, fields @ ..] = &line.split('\t').collect::<Vec<_>>()[..] {
+				if let Some(row) = T::parse(fields) {
+					table.rows.insert((scheme.to_string(), syllable.to_string()), row);
+				}
+			}
+		}
+		Ok(table)
+	}
+
+	/// Writes back the rows of every scheme, sorted
+	pub fn save(&self) -> Result<(), io::Error> {
+		let mut lines: Vec<_> = self.rows.iter().collect();
+		lines.sort_unstable_by(|a, b| a.0.cmp(b.0));
+		let mut file = String::new();
+		for ((scheme, syllable), row) in lines {
+			writeln!(file, "{}\t{}\t{}", scheme, syllable, row.fields()).unwrap();
+		}
+		self.store.write(self.name, &file)
+	}
+
+	pub fn get(&self, syllable: &str) -> Option<&T> {
+		self.rows.get(&(self.scheme.clone(), syllable.to_string()))
+	}
+
+	pub fn insert(&mut self, syllable: &str, row: T) {
+		self.rows.insert((self.scheme.clone(), syllable.to_string()), row);
+	}
+
+	/// The row of `syllable`, made by `new` if there is none
+	pub fn get_or_insert_with(&mut self, syllable: &str, new: impl FnOnce() -> T) -> &mut T {
+		self.rows.entry((self.scheme.clone(), syllable.to_string())).or_insert_with(new)
+	}
+
+	/// The rows of the scheme
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+		self.rows.iter()
+			.filter(|((scheme, _), _)| *scheme == self.scheme)
+			.map(|((_, syllable), row)| (syllable.as_str(), row))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tables_keep_every_scheme_and_skip_broken_lines() {
+		let dir = std::env::temp_dir().join(format!("spcli-store-test-{}", std::process::id()));
+		let store = Store::at(dir.clone());
+		store.write("table.tsv", "小鹤双拼\tan\t3\n自然码\tan\t1\nbroken\n小鹤双拼\tai\tx\n").unwrap();
+
+		let mut table: Table<u32> = Table::load(store.clone(), "table.tsv", "小鹤双拼").unwrap();
+		assert_eq!(table.get("an"), Some(&3));
+		assert_eq!(table.get("ai"), None);
+		*table.get_or_insert_with("ai", || 0) += 1;
+		table.insert("an", 0);
+		assert_eq!(table.iter().count(), 2);
+		table.save().unwrap();
+		store.append("table.tsv", "全拼\tai\t2").unwrap();
+
+		assert_eq!(store.read("table.tsv").unwrap(), "小鹤双拼\tai\t1\n小鹤双拼\tan\t0\n自然码\tan\t1\n全拼\tai\t2\n");
+		assert_eq!(store.read("missing.tsv").unwrap(), "");
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -0,0 +1,179 @@
+use crossterm::style::Color;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ThemeType {
+	Dark,
+	Light,
+	HighContrast,
+	Colorblind,
+}
+
+impl ThemeType {
+	pub fn theme(&self) -> Theme {
+		match self {
+			ThemeType::Dark			=> DARK,
+			ThemeType::Light		=> LIGHT,
+			ThemeType::HighContrast	=> HIGH_CONTRAST,
+			ThemeType::Colorblind	=> COLORBLIND,
+		}
+	}
+}
+
+/// Colors used by the renderer, grouped by the role they play on screen
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+	/// Characters that have been typed correctly
+	pub passed: Color,
+	/// Passed characters outside of the typing line
+	pub passed_dim: Color,
+	/// The character being typed
+	pub typing: Color,
+	/// The character whose initial has been typed
+	pub half_typed: Color,
+	/// Mistyped initials / finals
+	pub error: Color,
+	/// The label before the mistakes in the status bar
+	pub error_label: Color,
+	/// Characters waiting to be typed
+	pub future: Color,
+	/// Future characters outside of the typing line
+	pub future_dim: Color,
+	/// The bar marking the typing line
+	pub cursor: Color,
+
+	/// Borders of the passage box and the keys
+	pub key_border: Color,
+	/// The letter printed on a key
+	pub key_name: Color,
+	/// `zh` `ch` `sh`
+	pub initial_label: Color,
+	/// Finals printed on a key
+	pub final_label: Color,
+	/// Background of the next key(s) to press
+	pub highlight: Color,
+	/// Background of the key just pressed, when it was correct
+	pub highlight_pass: Color,
+	/// Background of the key just pressed, when it was wrong
+	pub highlight_error: Color,
+
+	/// Background of the status bar
+	pub status_bar: Color,
+	/// Plain text
+	pub text: Color,
+	/// Less important text, e.g. the `/` between typed and total
+	pub text_muted: Color,
+	/// `│` between the fields of the status bar
+	pub separator: Color,
+	/// Title of the summary
+	pub title: Color,
+	/// Figures in the summary
+	pub figure: Color,
+	/// Key bindings hint
+	pub hint: Color,
+}
+
+pub const DARK: Theme = Theme {
+	passed:				Color::Green,
+	passed_dim:			Color::DarkGreen,
+	typing:				Color::Blue,
+	half_typed:			Color::Cyan,
+	error:				Color::Red,
+	error_label:		Color::DarkRed,
+	future:				Color::White,
+	future_dim:			Color::Grey,
+	cursor:				Color::Cyan,
+	key_border:			Color::DarkBlue,
+	key_name:			Color::Green,
+	initial_label:		Color::Red,
+	final_label:		Color::Yellow,
+	highlight:			Color::DarkBlue,
+	highlight_pass:		Color::DarkGreen,
+	highlight_error:	Color::DarkRed,
+	status_bar:			Color::Rgb { r: 42, g: 47, b: 49 },
+	text:				Color::White,
+	text_muted:			Color::Grey,
+	separator:			Color::Blue,
+	title:				Color::Yellow,
+	figure:				Color::Cyan,
+	hint:				Color::DarkYellow,
+};
+
+pub const LIGHT: Theme = Theme {
+	passed:				Color::DarkGreen,
+	passed_dim:			Color::DarkGreen,
+	typing:				Color::DarkBlue,
+	half_typed:			Color::DarkCyan,
+	error:				Color::DarkRed,
+	error_label:		Color::DarkRed,
+	future:				Color::Black,
+	future_dim:			Color::DarkGrey,
+	cursor:				Color::DarkCyan,
+	key_border:			Color::DarkBlue,
+	key_name:			Color::DarkGreen,
+	initial_label:		Color::DarkRed,
+	final_label:		Color::DarkMagenta,
+	highlight:			Color::Rgb { r: 173, g: 216, b: 230 },
+	highlight_pass:		Color::Rgb { r: 178, g: 223, b: 178 },
+	highlight_error:	Color::Rgb { r: 240, g: 178, b: 178 },
+	status_bar:			Color::Rgb { r: 224, g: 224, b: 224 },
+	text:				Color::Black,
+	text_muted:			Color::DarkGrey,
+	separator:			Color::DarkBlue,
+	title:				Color::DarkMagenta,
+	figure:				Color::DarkBlue,
+	hint:				Color::DarkYellow,
+};
+
+pub const HIGH_CONTRAST: Theme = Theme {
+	passed:				Color::Rgb { r: 0, g: 255, b: 0 },
+	passed_dim:			Color::Rgb { r: 0, g: 255, b: 0 },
+	typing:				Color::Rgb { r: 0, g: 255, b: 255 },
+	half_typed:			Color::Rgb { r: 255, g: 255, b: 0 },
+	error:				Color::Rgb { r: 255, g: 0, b: 0 },
+	error_label:		Color::Rgb { r: 255, g: 0, b: 0 },
+	future:				Color::Rgb { r: 255, g: 255, b: 255 },
+	future_dim:			Color::Rgb { r: 255, g: 255, b: 255 },
+	cursor:				Color::Rgb { r: 255, g: 255, b: 0 },
+	key_border:			Color::Rgb { r: 255, g: 255, b: 255 },
+	key_name:			Color::Rgb { r: 255, g: 255, b: 255 },
+	initial_label:		Color::Rgb { r: 255, g: 0, b: 255 },
+	final_label:		Color::Rgb { r: 255, g: 255, b: 0 },
+	highlight:			Color::Rgb { r: 0, g: 0, b: 255 },
+	highlight_pass:		Color::Rgb { r: 0, g: 128, b: 0 },
+	highlight_error:	Color::Rgb { r: 192, g: 0, b: 0 },
+	status_bar:			Color::Rgb { r: 0, g: 0, b: 0 },
+	text:				Color::Rgb { r: 255, g: 255, b: 255 },
+	text_muted:			Color::Rgb { r: 255, g: 255, b: 255 },
+	separator:			Color::Rgb { r: 255, g: 255, b: 0 },
+	title:				Color::Rgb { r: 255, g: 255, b: 0 },
+	figure:				Color::Rgb { r: 0, g: 255, b: 255 },
+	hint:				Color::Rgb { r: 255, g: 255, b: 255 },
+};
+
+// Okabe-Ito palette, distinguishable under the common forms of color blindness.
+// Correct / wrong is blue / vermillion instead of green / red.
+pub const COLORBLIND: Theme = Theme {
+	passed:				Color::Rgb { r: 86, g: 180, b: 233 },
+	passed_dim:			Color::Rgb { r: 0, g: 114, b: 178 },
+	typing:				Color::Rgb { r: 240, g: 228, b: 66 },
+	half_typed:			Color::Rgb { r: 204, g: 121, b: 167 },
+	error:				Color::Rgb { r: 213, g: 94, b: 0 },
+	error_label:		Color::Rgb { r: 213, g: 94, b: 0 },
+	future:				Color::White,
+	future_dim:			Color::Grey,
+	cursor:				Color::Rgb { r: 240, g: 228, b: 66 },
+	key_border:			Color::Rgb { r: 0, g: 114, b: 178 },
+	key_name:			Color::Rgb { r: 86, g: 180, b: 233 },
+	initial_label:		Color::Rgb { r: 204, g: 121, b: 167 },
+	final_label:		Color::Rgb { r: 230, g: 159, b: 0 },
+	highlight:			Color::Rgb { r: 0, g: 114, b: 178 },
+	highlight_pass:		Color::Rgb { r: 0, g: 158, b: 115 },
+	highlight_error:	Color::Rgb { r: 213, g: 94, b: 0 },
+	status_bar:			Color::Rgb { r: 42, g: 47, b: 49 },
+	text:				Color::White,
+	text_muted:			Color::Grey,
+	separator:			Color::Rgb { r: 86, g: 180, b: 233 },
+	title:				Color::Rgb { r: 240, g: 228, b: 66 },
+	figure:				Color::Rgb { r: 86, g: 180, b: 233 },
+	hint:				Color::Rgb { r: 230, g: 159, b: 0 },
+};
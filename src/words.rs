@@ -0,0 +1,63 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use crate::hanzi;
+
+/// 分词用的常用词, 格式见文件开头
+const WORDS: &str = include_str!("words.txt");
+
+struct Dictionary {
+	words: HashSet<String>,
+	/// Characters of the longest word
+	longest: usize,
+}
+
+fn dictionary() -> &'static Dictionary {
+	static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+	DICTIONARY.get_or_init(|| {
+		let words: HashSet<String> = WORDS.lines()
+			.filter(|line| !line.trim().starts_with('#'))
+			.flat_map(|line| line.split_whitespace())
+			.map(|word| word.to_string())
+			.collect();
+		let longest = words.iter().map(|word| word.chars().count()).max().unwrap_or(1);
+		Dictionary { words, longest }
+	})
+}
+
+/// Whether `chars` make a word of the dictionary, read in their Simplified form
+fn is_word(chars: &[char]) -> bool {
+	let word: String = chars.iter().copied().map(hanzi::to_simplified).collect();
+	dictionary().words.contains(&word)
+}
+
+/// The lengths of the words `chars` are made of, in order
+///
+/// A character not in any word of the dictionary is a word by itself. Of the ways to cut
+/// `chars`, the one with the fewest words wins, then the one with the fewest characters
+/// left alone, `研究 生命` rather than `研究生 命`, then the one with the longer words first.
+pub fn segment(chars: &[char]) -> Vec<usize> {
+	let longest = dictionary().longest;
+	// `best[i]`: the words and the lone characters `chars[i..]` is cut into at best, and
+	// the length of its first word
+	let mut best = vec![((0usize, 0usize), 0usize); chars.len() + 1];
+	for start in (0..chars.len()).rev() {
+		best[start] = ((usize::MAX, usize::MAX), 1);
+		for len in 1..=longest.min(chars.len() - start) {
+			if len > 1 && !is_word(&chars[start..start + len]) {
+				continue;
+			}
+			let ((words, singles), _) = best[start + len];
+			let cost = (words + 1, singles + (len == 1) as usize);
+			if cost <= best[start].0 {
+				best[start] = (cost, len);
+			}
+		}
+	}
+	let mut result = Vec::new();
+	let mut start = 0;
+	while start < chars.len() {
+		result.push(best[start].1);
+		start += best[start].1;
+	}
+	result
+}
@@ -0,0 +1,46 @@
+//! Runs every `scripts/<scheme>[-<name>].keys` with `keymap/<scheme>` on the text of the same
+//! name if there is one, `scripts/zero.txt` otherwise, and compares the report with
+//! `scripts/<scheme>[-<name>].out`, timings left out.
+
+use std::{fs, path::Path, process::Command};
+
+#[test]
+fn scripts_print_the_expected_reports() {
+	let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let scripts = root.join("tests/scripts");
+	// Neither the user's config nor their progress may change the results
+	let home = std::env::temp_dir().join(format!("spcli-script-test-{}", std::process::id()));
+	fs::create_dir_all(&home).unwrap();
+
+	let mut ran = 0;
+	for entry in fs::read_dir(&scripts).unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().is_none_or(|ext| ext != "keys") {
+			continue;
+		}
+		let name = path.file_stem().unwrap().to_str().unwrap();
+		let scheme = name.split('-').next().unwrap();
+		let output = Command::new(env!("CARGO_BIN_EXE_spcli"))
+			.current_dir(root)
+			.env("XDG_CONFIG_HOME", &home)
+			.env("XDG_DATA_HOME", &home)
+			.env("HOME", &home)
+			.arg("-t").arg(Some(path.with_extension("txt")).filter(|text| text.exists()).unwrap_or_else(|| scripts.join("zero.txt")))
+			.arg("-k").arg(root.join("keymap").join(scheme))
+			.arg("script").arg(&path)
+			.output()
+			.unwrap();
+		assert!(output.status.success(), "{}: {}", name, String::from_utf8_lossy(&output.stderr));
+		let report: String = String::from_utf8(output.stdout).unwrap()
+			.lines()
+			.filter(|line| !line.starts_with("reading:") && !line.starts_with("time:"))
+			.map(|line| format!("{}\n", line))
+			.collect();
+		let expected = fs::read_to_string(path.with_extension("out")).unwrap();
+		assert_eq!(report, expected, "{}", name);
+		ran += 1;
+	}
+	assert!(ran > 0);
+	assert!(fs::read_dir(&home).unwrap().next().is_none(), "a script run wrote to the data directory");
+	fs::remove_dir(&home).unwrap();
+}